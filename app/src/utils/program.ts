@@ -105,6 +105,69 @@ export const getMarketPDA = (authority: PublicKey, seed: string): [PublicKey, nu
   );
 };
 
+// Whitespace collapsed by normalizeQuestion: space, tab, line feed, form
+// feed and carriage return, the same set as Market::QUESTION_WHITESPACE.
+// JS `\s` also matches Unicode spaces, which the program keeps.
+const QUESTION_WHITESPACE = /[ \t\n\f\r]+/;
+
+// Canonical question text, matching Market::normalize_question on-chain
+export const normalizeQuestion = (question: string): string => {
+  return question
+    .split(QUESTION_WHITESPACE)
+    .filter((word) => word.length > 0)
+    .join(' ')
+    .replace(/[A-Z]/g, (c) => c.toLowerCase());
+};
+
+// Hash of the market parameters used as the market PDA seed
+export const getQuestionHash = async (
+  question: string,
+  endTime: number,
+  oracle: PublicKey,
+  collateralMint: PublicKey
+): Promise<Buffer> => {
+  const endTimeBytes = Buffer.alloc(8);
+  endTimeBytes.writeBigInt64LE(BigInt(endTime));
+
+  const data = Buffer.concat([
+    Buffer.from(normalizeQuestion(question), 'utf8'),
+    endTimeBytes,
+    oracle.toBuffer(),
+    collateralMint.toBuffer()
+  ]);
+
+  return Buffer.from(await crypto.subtle.digest('SHA-256', data));
+};
+
+// Derive market PDA from its question parameters
+export const getQuestionMarketPDA = async (
+  question: string,
+  endTime: number,
+  oracle: PublicKey,
+  collateralMint: PublicKey
+): Promise<[PublicKey, number]> => {
+  const questionHash = await getQuestionHash(question, endTime, oracle, collateralMint);
+  return PublicKey.findProgramAddressSync(
+    [
+      Buffer.from('market'),
+      questionHash
+    ],
+    PROGRAM_ID
+  );
+};
+
+// Look up an existing market for a question without scanning program accounts
+export const findMarketForQuestion = async (
+  question: string,
+  endTime: number,
+  oracle: PublicKey,
+  collateralMint: PublicKey
+): Promise<PublicKey | null> => {
+  const [marketPDA] = await getQuestionMarketPDA(question, endTime, oracle, collateralMint);
+  const accountInfo = await connection.getAccountInfo(marketPDA);
+  return accountInfo ? marketPDA : null;
+};
+
 // Derive position PDA
 export const getPositionPDA = (user: PublicKey, market: PublicKey): [PublicKey, number] => {
   return PublicKey.findProgramAddressSync(
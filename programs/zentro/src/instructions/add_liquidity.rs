@@ -1,18 +1,27 @@
 ```rust
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token_interface::{self, Mint, MintTo, TokenAccount, TokenInterface};
 
 use crate::state::*;
-use crate::errors::*;
+use crate::utils::collateral;
+use crate::{ErrorCode, Market, ProtocolConfig};
 
 #[derive(Accounts)]
 pub struct AddLiquidity<'info> {
+    #[account(mut)]
+    pub market: Box<Account<'info, Market>>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Box<Account<'info, ProtocolConfig>>,
+
     #[account(
         mut,
-        seeds = [b"market", market.market_id.to_le_bytes().as_ref()],
-        bump = market.bump,
+        seeds = [b"pool", market.key().as_ref()],
+        bump = pool.bump,
+        has_one = market,
     )]
-    pub market: Account<'info, Market>,
+    pub pool: Box<Account<'info, LiquidityPool>>,
 
     #[account(
         init_if_needed,
@@ -21,93 +30,182 @@ pub struct AddLiquidity<'info> {
         seeds = [b"liquidity", market.key().as_ref(), user.key().as_ref()],
         bump,
     )]
-    pub liquidity_position: Account<'info, LiquidityPosition>,
+    pub liquidity_position: Box<Account<'info, LiquidityPosition>>,
+
+    /// Source of SPL collateral. Not needed for native SOL markets.
+    #[account(mut)]
+    pub user_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
 
     #[account(
         mut,
-        constraint = user_token_account.mint == market.token_mint,
-        constraint = user_token_account.owner == user.key(),
+        seeds = [b"vault", market.key().as_ref()],
+        bump,
     )]
-    pub user_token_account: Account<'info, TokenAccount>,
+    pub market_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(address = market.collateral_mint)]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
 
+    #[account(mut, address = market.yes_mint @ ErrorCode::InvalidOutcomeMint)]
+    pub yes_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(mut, address = market.no_mint @ ErrorCode::InvalidOutcomeMint)]
+    pub no_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(mut, address = pool.yes_token_vault)]
+    pub pool_yes_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut, address = pool.no_token_vault)]
+    pub pool_no_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut, address = pool.lp_mint @ ErrorCode::InvalidLpMint)]
+    pub lp_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// New LP tokens are minted here, staked for the provider.
+    #[account(mut, address = pool.lp_vault)]
+    pub lp_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Receives the YES surplus when the pool is not balanced.
     #[account(
-        mut,
-        seeds = [b"vault", market.key().as_ref()],
-        bump = market.vault_bump,
-        constraint = market_vault.mint == market.token_mint,
+        init_if_needed,
+        payer = user,
+        associated_token::mint = yes_mint,
+        associated_token::authority = user,
+        associated_token::token_program = token_program,
+    )]
+    pub user_yes_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Receives the NO surplus when the pool is not balanced.
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = no_mint,
+        associated_token::authority = user,
+        associated_token::token_program = token_program,
     )]
-    pub market_vault: Account<'info, TokenAccount>,
+    pub user_no_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Holding checked against a token-gated market's access policy.
+    pub gate_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
 
     #[account(mut)]
     pub user: Signer<'info>,
 
-    pub token_program: Program<'info, Token>,
-    pub system_program: Program<'info, SystemProgram>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
 }
 
 impl<'info> AddLiquidity<'info> {
-    pub fn add_liquidity(&mut self, amount: u64, bumps: &AddLiquidityBumps) -> Result<()> {
-        require!(amount > 0, ZentroError::InvalidAmount);
-        require!(self.market.is_active, ZentroError::MarketNotActive);
-
-        // Calculate liquidity shares to mint
-        let total_liquidity = self.market.total_liquidity;
-        let vault_balance = self.market_vault.amount;
-
-        let shares_to_mint = if total_liquidity == 0 {
-            // First liquidity provider gets shares equal to amount
-            amount
-        } else {
-            // Calculate proportional shares
-            (amount as u128)
-                .checked_mul(total_liquidity as u128)
-                .ok_or(ZentroError::MathOverflow)?
-                .checked_div(vault_balance as u128)
-                .ok_or(ZentroError::MathOverflow)? as u64
-        };
-
-        require!(shares_to_mint > 0, ZentroError::InsufficientLiquidity);
-
-        // Transfer tokens from user to market vault
-        let transfer_ctx = CpiContext::new(
-            self.token_program.to_account_info(),
-            Transfer {
-                from: self.user_token_account.to_account_info(),
-                to: self.market_vault.to_account_info(),
-                authority: self.user.to_account_info(),
-            },
-        );
-        token::transfer(transfer_ctx, amount)?;
-
-        // Update liquidity position
-        if self.liquidity_position.market == Pubkey::default() {
-            // Initialize new position
-            self.liquidity_position.market = self.market.key();
-            self.liquidity_position.owner = self.user.key();
-            self.liquidity_position.shares = shares_to_mint;
-            self.liquidity_position.bump = bumps.liquidity_position;
-        } else {
-            // Add to existing position
-            self.liquidity_position.shares = self
-                .liquidity_position
-                .shares
-                .checked_add(shares_to_mint)
-                .ok_or(ZentroError::MathOverflow)?;
+    pub fn add_liquidity(
+        &mut self,
+        amount: u64,
+        proof: &[[u8; 32]],
+        hook_accounts: &[AccountInfo<'info>],
+        bumps: &AddLiquidityBumps,
+    ) -> Result<()> {
+        require!(amount > 0, ErrorCode::InvalidAmount);
+        require!(!self.config.paused, ErrorCode::ProtocolPaused);
+        require!(!self.market.resolved, ErrorCode::MarketResolved);
+        let clock = Clock::get()?;
+        self.market.schedule.require_trading_open(&clock)?;
+        self.market.access_policy.check(
+            &self.user.key(),
+            proof,
+            self.gate_token_account.as_deref(),
+        )?;
+
+        // Transfer collateral from user to market vault
+        let user_token_account = self
+            .user_token_account
+            .as_ref()
+            .map(|account| account.to_account_info());
+        let received = collateral::deposit(
+            &self.token_program.to_account_info(),
+            &self.system_program.to_account_info(),
+            &self.mint,
+            &mut self.market_vault,
+            &self.user.to_account_info(),
+            user_token_account.as_ref(),
+            hook_accounts,
+            amount,
+        )?;
+
+        // The deposit becomes complete sets, split between the reserves and
+        // the provider so the pool price does not move
+        let lp_supply = self.lp_mint.supply;
+        self.pool.accumulate_price(clock.unix_timestamp)?;
+        let (shares_to_mint, yes_added, no_added) = self.pool.add_liquidity(received, lp_supply)?;
+
+        let seeds = self.market.signer_seeds();
+        let signer = &[&seeds[..]];
+        for (mint, pool_vault, user_account, added) in [
+            (&self.yes_mint, &self.pool_yes_vault, &self.user_yes_account, yes_added),
+            (&self.no_mint, &self.pool_no_vault, &self.user_no_account, no_added),
+        ] {
+            for (to, tokens) in [(pool_vault, added), (user_account, received - added)] {
+                if tokens == 0 {
+                    continue;
+                }
+                token_interface::mint_to(
+                    CpiContext::new_with_signer(
+                        self.token_program.to_account_info(),
+                        MintTo {
+                            mint: mint.to_account_info(),
+                            to: to.to_account_info(),
+                            authority: self.market.to_account_info(),
+                        },
+                        signer,
+                    ),
+                    tokens,
+                )?;
+            }
         }
 
+        let pool_seeds = self.pool.signer_seeds();
+        token_interface::mint_to(
+            CpiContext::new_with_signer(
+                self.token_program.to_account_info(),
+                MintTo {
+                    mint: self.lp_mint.to_account_info(),
+                    to: self.lp_vault.to_account_info(),
+                    authority: self.pool.to_account_info(),
+                },
+                &[&pool_seeds[..]],
+            ),
+            shares_to_mint,
+        )?;
+
         // Update market state
-        self.market.total_liquidity = self
+        self.market.total_yes_tokens = self
+            .market
+            .total_yes_tokens
+            .checked_add(received)
+            .ok_or(ErrorCode::MathOverflow)?;
+        self.market.total_no_tokens = self
             .market
-            .total_liquidity
-            .checked_add(shares_to_mint)
-            .ok_or(ZentroError::MathOverflow)?;
+            .total_no_tokens
+            .checked_add(received)
+            .ok_or(ErrorCode::MathOverflow)?;
+        self.market.total_collateral = self
+            .market
+            .total_collateral
+            .checked_add(received)
+            .ok_or(ErrorCode::MathOverflow)?;
+
+        // The new tokens start out staked, so they earn from now on
+        self.liquidity_position
+            .open(self.market.key(), self.user.key(), bumps.liquidity_position);
+        self.liquidity_position
+            .stake(self.pool.fee_per_share, shares_to_mint)?;
+        self.pool.stake(shares_to_mint)?;
 
         emit!(LiquidityAddedEvent {
             market: self.market.key(),
             user: self.user.key(),
-            amount,
+            amount: received,
             shares: shares_to_mint,
-            total_liquidity: self.market.total_liquidity,
+            total_liquidity: lp_supply + shares_to_mint,
         });
 
         Ok(())
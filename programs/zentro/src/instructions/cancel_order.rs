@@ -0,0 +1,65 @@
+use anchor_lang::prelude::*;
+
+use crate::state::*;
+use crate::{ErrorCode, Market};
+
+#[derive(Accounts)]
+#[instruction(outcome: bool)]
+pub struct CancelOrder<'info> {
+    pub market: Box<Account<'info, Market>>,
+
+    #[account(
+        mut,
+        seeds = [b"order_book", market.key().as_ref(), &[outcome as u8]],
+        bump = order_book.load()?.bump,
+        has_one = market,
+    )]
+    pub order_book: AccountLoader<'info, OrderBook>,
+
+    #[account(
+        mut,
+        seeds = [b"open_orders", market.key().as_ref(), user.key().as_ref()],
+        bump = open_orders.bump,
+    )]
+    pub open_orders: Box<Account<'info, OpenOrders>>,
+
+    pub user: Signer<'info>,
+}
+
+impl<'info> CancelOrder<'info> {
+    pub fn cancel_order(&mut self, outcome: bool, order_id: u64) -> Result<()> {
+        let mut book = self.order_book.load_mut()?;
+        let (side, order) = book.remove(order_id)?;
+        require_keys_eq!(order.open_orders, self.open_orders.key(), ErrorCode::UnauthorizedUser);
+
+        // Funds stay in the open-orders account until settled
+        match side {
+            Side::Bid => self
+                .open_orders
+                .unlock_collateral(OrderBook::notional(order.price, order.quantity)?)?,
+            Side::Ask => self.open_orders.unlock_outcome(outcome, order.quantity)?,
+        }
+
+        emit!(OrderCancelled {
+            market: self.market.key(),
+            outcome,
+            order_id,
+            owner: self.user.key(),
+            side,
+            quantity: order.quantity,
+        });
+
+        Ok(())
+    }
+}
+
+#[event]
+pub struct OrderCancelled {
+    pub market: Pubkey,
+    pub outcome: bool,
+    pub order_id: u64,
+    pub owner: Pubkey,
+    pub side: Side,
+    /// Unfilled quantity that was removed.
+    pub quantity: u64,
+}
@@ -0,0 +1,46 @@
+use anchor_lang::prelude::*;
+
+use crate::state::*;
+
+#[derive(Accounts)]
+#[instruction(nonce: u64)]
+pub struct CancelSignedOrder<'info> {
+    #[account(
+        init_if_needed,
+        payer = maker,
+        space = 8 + OrderStatus::INIT_SPACE,
+        seeds = [b"order_status", maker.key().as_ref(), &nonce.to_le_bytes()],
+        bump,
+    )]
+    pub order_status: Box<Account<'info, OrderStatus>>,
+
+    #[account(mut)]
+    pub maker: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> CancelSignedOrder<'info> {
+    /// Stops any unfilled part of the maker's signed order with `nonce`
+    /// from settling. Works before the order has ever been matched.
+    pub fn cancel_signed_order(&mut self, nonce: u64, bumps: &CancelSignedOrderBumps) -> Result<()> {
+        self.order_status.open(self.maker.key(), nonce, bumps.order_status);
+        self.order_status.cancel();
+
+        emit!(SignedOrderCancelled {
+            maker: self.maker.key(),
+            nonce,
+            filled: self.order_status.filled,
+        });
+
+        Ok(())
+    }
+}
+
+#[event]
+pub struct SignedOrderCancelled {
+    pub maker: Pubkey,
+    pub nonce: u64,
+    /// Quantity that had already filled.
+    pub filled: u64,
+}
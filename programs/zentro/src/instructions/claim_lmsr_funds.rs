@@ -0,0 +1,84 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use crate::state::*;
+use crate::utils::collateral;
+use crate::{ErrorCode, Market};
+
+#[derive(Accounts)]
+pub struct ClaimLmsrFunds<'info> {
+    #[account(has_one = creator @ ErrorCode::UnauthorizedUser)]
+    pub market: Box<Account<'info, Market>>,
+
+    #[account(
+        mut,
+        seeds = [b"lmsr", market.key().as_ref()],
+        bump = lmsr_pool.bump,
+        has_one = market,
+    )]
+    pub lmsr_pool: Box<Account<'info, LmsrPool>>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", market.key().as_ref()],
+        bump,
+    )]
+    pub market_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub creator_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(address = market.collateral_mint)]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(mut)]
+    pub creator: Signer<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+impl<'info> ClaimLmsrFunds<'info> {
+    pub fn claim_lmsr_funds(
+        &mut self,
+        hook_accounts: &[AccountInfo<'info>],
+        bumps: &ClaimLmsrFundsBumps,
+    ) -> Result<()> {
+        // The surplus is only known once the winning side is
+        let surplus = self.market.outcome.map(|_| self.market.lmsr_surplus());
+        let fees = self.lmsr_pool.accumulated_fees;
+        let amount = self.lmsr_pool.take_creator_funds(surplus)?;
+        require!(amount > 0, ErrorCode::NoFeesToClaim);
+
+        // Fees sit in the vault outside total_collateral, and the surplus is
+        // outside the redemption pot
+        let market_key = self.market.key();
+        let seeds = &[b"vault", market_key.as_ref(), &[bumps.market_vault]];
+        collateral::withdraw(
+            &self.token_program.to_account_info(),
+            &self.market_vault,
+            &self.mint,
+            &self.creator.to_account_info(),
+            &self.creator_token_account.to_account_info(),
+            hook_accounts,
+            amount,
+            &[&seeds[..]],
+        )?;
+
+        emit!(LmsrFundsClaimed {
+            market: market_key,
+            creator: self.creator.key(),
+            fees,
+            surplus: amount - fees,
+        });
+
+        Ok(())
+    }
+}
+
+#[event]
+pub struct LmsrFundsClaimed {
+    pub market: Pubkey,
+    pub creator: Pubkey,
+    pub fees: u64,
+    pub surplus: u64,
+}
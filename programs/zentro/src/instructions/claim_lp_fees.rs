@@ -0,0 +1,89 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use crate::state::*;
+use crate::utils::collateral;
+use crate::{ErrorCode, Market};
+
+#[derive(Accounts)]
+pub struct ClaimLpFees<'info> {
+    pub market: Box<Account<'info, Market>>,
+
+    #[account(
+        mut,
+        seeds = [b"pool", market.key().as_ref()],
+        bump = pool.bump,
+        has_one = market,
+    )]
+    pub pool: Box<Account<'info, LiquidityPool>>,
+
+    #[account(
+        mut,
+        seeds = [b"liquidity", market.key().as_ref(), user.key().as_ref()],
+        bump = liquidity_position.bump,
+    )]
+    pub liquidity_position: Box<Account<'info, LiquidityPosition>>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", market.key().as_ref()],
+        bump,
+    )]
+    pub market_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub user_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(address = market.collateral_mint)]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+impl<'info> ClaimLpFees<'info> {
+    pub fn claim_lp_fees(
+        &mut self,
+        hook_accounts: &[AccountInfo<'info>],
+        bumps: &ClaimLpFeesBumps,
+    ) -> Result<()> {
+        self.liquidity_position.settle_fees(self.pool.fee_per_share)?;
+
+        let amount = self.liquidity_position.pending_fees;
+        require!(amount > 0, ErrorCode::NoFeesToClaim);
+
+        self.liquidity_position.pending_fees = 0;
+        self.pool.release_lp_fees(amount)?;
+
+        // Fees were left in the market vault outside total_collateral
+        let market_key = self.market.key();
+        let seeds = &[b"vault", market_key.as_ref(), &[bumps.market_vault]];
+        collateral::withdraw(
+            &self.token_program.to_account_info(),
+            &self.market_vault,
+            &self.mint,
+            &self.user.to_account_info(),
+            &self.user_token_account.to_account_info(),
+            hook_accounts,
+            amount,
+            &[&seeds[..]],
+        )?;
+
+        emit!(LpFeesClaimed {
+            market: market_key,
+            user: self.user.key(),
+            amount,
+        });
+
+        Ok(())
+    }
+}
+
+#[event]
+pub struct LpFeesClaimed {
+    pub market: Pubkey,
+    pub user: Pubkey,
+    pub amount: u64,
+}
@@ -0,0 +1,141 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{self, Mint, TokenAccount, TokenInterface, TransferChecked};
+
+use crate::state::*;
+use crate::utils::collateral;
+use crate::{ErrorCode, Market};
+
+#[derive(Accounts)]
+#[instruction(outcome: bool)]
+pub struct DepositFunds<'info> {
+    #[account(mut)]
+    pub market: Box<Account<'info, Market>>,
+
+    #[account(
+        seeds = [b"order_book", market.key().as_ref(), &[outcome as u8]],
+        bump = order_book.load()?.bump,
+        has_one = market,
+    )]
+    pub order_book: AccountLoader<'info, OrderBook>,
+
+    #[account(mut, address = order_book.load()?.token_vault)]
+    pub book_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = 8 + OpenOrders::INIT_SPACE,
+        seeds = [b"open_orders", market.key().as_ref(), user.key().as_ref()],
+        bump,
+    )]
+    pub open_orders: Box<Account<'info, OpenOrders>>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", market.key().as_ref()],
+        bump,
+    )]
+    pub market_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Source of SPL collateral. Not needed for native SOL markets.
+    #[account(mut)]
+    pub user_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(address = market.collateral_mint)]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(address = market.outcome_mint(outcome) @ ErrorCode::InvalidOutcomeMint)]
+    pub outcome_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// Source of outcome tokens. Only needed when depositing tokens.
+    #[account(
+        mut,
+        token::mint = outcome_mint,
+        token::authority = user,
+    )]
+    pub user_outcome_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> DepositFunds<'info> {
+    /// Adds collateral and this book's outcome tokens to the free balances
+    /// of the user's open-orders account.
+    pub fn deposit_funds(
+        &mut self,
+        outcome: bool,
+        collateral_amount: u64,
+        token_amount: u64,
+        hook_accounts: &[AccountInfo<'info>],
+        bumps: &DepositFundsBumps,
+    ) -> Result<()> {
+        require!(collateral_amount > 0 || token_amount > 0, ErrorCode::InvalidAmount);
+
+        self.open_orders
+            .open(self.market.key(), self.user.key(), bumps.open_orders);
+
+        let mut received = 0;
+        if collateral_amount > 0 {
+            let user_token_account = self
+                .user_token_account
+                .as_ref()
+                .map(|account| account.to_account_info());
+            received = collateral::deposit(
+                &self.token_program.to_account_info(),
+                &self.system_program.to_account_info(),
+                &self.mint,
+                &mut self.market_vault,
+                &self.user.to_account_info(),
+                user_token_account.as_ref(),
+                hook_accounts,
+                collateral_amount,
+            )?;
+            self.open_orders.credit_collateral(received)?;
+            self.market.book_collateral = self.market.book_collateral.checked_add(received).ok_or(ErrorCode::MathOverflow)?;
+        }
+
+        if token_amount > 0 {
+            let user_outcome_account = self
+                .user_outcome_account
+                .as_ref()
+                .ok_or(ErrorCode::MissingUserTokenAccount)?;
+            token_interface::transfer_checked(
+                CpiContext::new(
+                    self.token_program.to_account_info(),
+                    TransferChecked {
+                        from: user_outcome_account.to_account_info(),
+                        mint: self.outcome_mint.to_account_info(),
+                        to: self.book_vault.to_account_info(),
+                        authority: self.user.to_account_info(),
+                    },
+                ),
+                token_amount,
+                self.outcome_mint.decimals,
+            )?;
+            self.open_orders.credit_outcome(outcome, token_amount)?;
+        }
+
+        emit!(FundsDeposited {
+            market: self.market.key(),
+            owner: self.user.key(),
+            outcome,
+            collateral: received,
+            tokens: token_amount,
+        });
+
+        Ok(())
+    }
+}
+
+#[event]
+pub struct FundsDeposited {
+    pub market: Pubkey,
+    pub owner: Pubkey,
+    pub outcome: bool,
+    pub collateral: u64,
+    pub tokens: u64,
+}
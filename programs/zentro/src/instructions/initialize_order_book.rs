@@ -0,0 +1,57 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use crate::state::*;
+use crate::{ErrorCode, Market};
+
+#[derive(Accounts)]
+#[instruction(outcome: bool)]
+pub struct InitializeOrderBook<'info> {
+    #[account(has_one = creator @ ErrorCode::UnauthorizedUser)]
+    pub market: Box<Account<'info, Market>>,
+
+    #[account(
+        init,
+        payer = creator,
+        space = OrderBook::LEN,
+        seeds = [b"order_book", market.key().as_ref(), &[outcome as u8]],
+        bump,
+    )]
+    pub order_book: AccountLoader<'info, OrderBook>,
+
+    #[account(address = market.outcome_mint(outcome) @ ErrorCode::InvalidOutcomeMint)]
+    pub outcome_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        init,
+        payer = creator,
+        token::mint = outcome_mint,
+        token::authority = order_book,
+        token::token_program = token_program,
+        seeds = [b"book_vault", order_book.key().as_ref()],
+        bump,
+    )]
+    pub book_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub creator: Signer<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> InitializeOrderBook<'info> {
+    pub fn initialize_order_book(&mut self, outcome: bool, bumps: &InitializeOrderBookBumps) -> Result<()> {
+        require!(!self.market.resolved, ErrorCode::MarketResolved);
+
+        self.order_book.load_init()?.initialize(
+            self.market.key(),
+            self.outcome_mint.key(),
+            self.book_vault.key(),
+            outcome,
+            bumps.order_book,
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,34 @@
+use anchor_lang::prelude::*;
+
+use crate::state::*;
+use crate::{ErrorCode, Market};
+
+#[derive(Accounts)]
+pub struct InitializePriceHistory<'info> {
+    #[account(mut, has_one = creator @ ErrorCode::UnauthorizedUser)]
+    pub market: Box<Account<'info, Market>>,
+
+    #[account(
+        init,
+        payer = creator,
+        space = PriceHistory::LEN,
+        seeds = [b"price_history", market.key().as_ref()],
+        bump,
+    )]
+    pub price_history: AccountLoader<'info, PriceHistory>,
+
+    #[account(mut)]
+    pub creator: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> InitializePriceHistory<'info> {
+    pub fn initialize_price_history(&mut self, interval: i64, bumps: &InitializePriceHistoryBumps) -> Result<()> {
+        self.price_history
+            .load_init()?
+            .initialize(self.market.key(), interval, bumps.price_history)?;
+        self.market.has_price_history = true;
+        Ok(())
+    }
+}
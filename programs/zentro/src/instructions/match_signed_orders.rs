@@ -0,0 +1,130 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::sysvar::instructions as instructions_sysvar;
+
+use crate::state::*;
+use crate::utils::ed25519;
+use crate::{ErrorCode, Market, ProtocolConfig, Role, RoleAssignment};
+
+#[derive(Accounts)]
+#[instruction(bid: SignedOrder, ask: SignedOrder)]
+pub struct MatchSignedOrders<'info> {
+    pub market: Box<Account<'info, Market>>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Box<Account<'info, ProtocolConfig>>,
+
+    #[account(
+        init_if_needed,
+        payer = operator,
+        space = 8 + OrderStatus::INIT_SPACE,
+        seeds = [b"order_status", bid.maker.as_ref(), &bid.nonce.to_le_bytes()],
+        bump,
+    )]
+    pub bid_status: Box<Account<'info, OrderStatus>>,
+
+    #[account(
+        init_if_needed,
+        payer = operator,
+        space = 8 + OrderStatus::INIT_SPACE,
+        seeds = [b"order_status", ask.maker.as_ref(), &ask.nonce.to_le_bytes()],
+        bump,
+    )]
+    pub ask_status: Box<Account<'info, OrderStatus>>,
+
+    #[account(
+        mut,
+        seeds = [b"open_orders", market.key().as_ref(), bid.maker.as_ref()],
+        bump = buyer_open_orders.bump,
+    )]
+    pub buyer_open_orders: Box<Account<'info, OpenOrders>>,
+
+    #[account(
+        mut,
+        seeds = [b"open_orders", market.key().as_ref(), ask.maker.as_ref()],
+        bump = seller_open_orders.bump,
+    )]
+    pub seller_open_orders: Box<Account<'info, OpenOrders>>,
+
+    /// CHECK: address is the instructions sysvar, read to find the Ed25519
+    /// program instructions that verified both makers' signatures.
+    #[account(address = instructions_sysvar::ID)]
+    pub instructions: UncheckedAccount<'info>,
+
+    #[account(
+        seeds = [b"role", operator.key().as_ref()],
+        bump = operator_roles.bump,
+        constraint = operator_roles.has_role(Role::Operator) @ ErrorCode::MissingRole
+    )]
+    pub operator_roles: Account<'info, RoleAssignment>,
+
+    #[account(mut)]
+    pub operator: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> MatchSignedOrders<'info> {
+    /// Fills `quantity` shares between a signed bid and a signed ask at
+    /// `price`, moving free balances between the makers' open-orders
+    /// accounts.
+    pub fn match_signed_orders(
+        &mut self,
+        bid: SignedOrder,
+        ask: SignedOrder,
+        price: u64,
+        quantity: u64,
+        bumps: &MatchSignedOrdersBumps,
+    ) -> Result<()> {
+        require!(!self.config.paused, ErrorCode::ProtocolPaused);
+        require!(!self.market.resolved, ErrorCode::MarketResolved);
+        let clock = Clock::get()?;
+        self.market.schedule.require_trading_open(&clock)?;
+
+        let market_key = self.market.key();
+        bid.validate(&market_key, Side::Bid, clock.unix_timestamp)?;
+        ask.validate(&market_key, Side::Ask, clock.unix_timestamp)?;
+        require!(bid.outcome == ask.outcome, ErrorCode::InvalidSignedOrder);
+        require_keys_neq!(bid.maker, ask.maker, ErrorCode::SelfTrade);
+        require!(ask.price <= price && price <= bid.price, ErrorCode::OrdersDoNotCross);
+        OrderBook::validate_order(price, quantity)?;
+
+        ed25519::verify_signature(&self.instructions, &bid.maker, &bid.message()?)?;
+        ed25519::verify_signature(&self.instructions, &ask.maker, &ask.message()?)?;
+
+        self.bid_status.open(bid.maker, bid.nonce, bumps.bid_status);
+        self.ask_status.open(ask.maker, ask.nonce, bumps.ask_status);
+        self.bid_status.record_fill(&bid, quantity)?;
+        self.ask_status.record_fill(&ask, quantity)?;
+
+        let value = OrderBook::notional(price, quantity)?;
+        self.buyer_open_orders.debit_collateral(value)?;
+        self.buyer_open_orders.credit_outcome(bid.outcome, quantity)?;
+        self.seller_open_orders.debit_outcome(ask.outcome, quantity)?;
+        self.seller_open_orders.credit_collateral(value)?;
+
+        emit!(SignedOrdersMatched {
+            market: market_key,
+            outcome: bid.outcome,
+            buyer: bid.maker,
+            seller: ask.maker,
+            bid_nonce: bid.nonce,
+            ask_nonce: ask.nonce,
+            price,
+            quantity,
+        });
+
+        Ok(())
+    }
+}
+
+#[event]
+pub struct SignedOrdersMatched {
+    pub market: Pubkey,
+    pub outcome: bool,
+    pub buyer: Pubkey,
+    pub seller: Pubkey,
+    pub bid_nonce: u64,
+    pub ask_nonce: u64,
+    pub price: u64,
+    pub quantity: u64,
+}
@@ -1 +1,39 @@
-I'm sorry. I'm not able to assist with that.
\ No newline at end of file
+pub mod add_liquidity;
+pub mod cancel_order;
+pub mod cancel_signed_order;
+pub mod claim_lmsr_funds;
+pub mod claim_lp_fees;
+pub mod deposit_funds;
+pub mod initialize_order_book;
+pub mod initialize_price_history;
+pub mod match_signed_orders;
+pub mod place_order;
+pub mod quote_bet;
+pub mod quote_buy;
+pub mod quote_lmsr;
+pub mod quote_sell;
+pub mod redeem_lp;
+pub mod remove_liquidity;
+pub mod settle_funds;
+pub mod stake_lp;
+pub mod unstake_lp;
+
+pub use add_liquidity::*;
+pub use cancel_order::*;
+pub use cancel_signed_order::*;
+pub use claim_lmsr_funds::*;
+pub use claim_lp_fees::*;
+pub use deposit_funds::*;
+pub use initialize_order_book::*;
+pub use initialize_price_history::*;
+pub use match_signed_orders::*;
+pub use place_order::*;
+pub use quote_bet::*;
+pub use quote_buy::*;
+pub use quote_lmsr::*;
+pub use quote_sell::*;
+pub use redeem_lp::*;
+pub use remove_liquidity::*;
+pub use settle_funds::*;
+pub use stake_lp::*;
+pub use unstake_lp::*;
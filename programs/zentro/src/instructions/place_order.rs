@@ -0,0 +1,236 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{self, Mint, TokenAccount, TokenInterface, TransferChecked};
+
+use crate::state::*;
+use crate::utils::collateral;
+use crate::{ErrorCode, Market, ProtocolConfig};
+
+#[derive(Accounts)]
+#[instruction(outcome: bool)]
+pub struct PlaceOrder<'info> {
+    #[account(mut)]
+    pub market: Box<Account<'info, Market>>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Box<Account<'info, ProtocolConfig>>,
+
+    #[account(
+        mut,
+        seeds = [b"order_book", market.key().as_ref(), &[outcome as u8]],
+        bump = order_book.load()?.bump,
+        has_one = market,
+    )]
+    pub order_book: AccountLoader<'info, OrderBook>,
+
+    #[account(mut, address = order_book.load()?.token_vault)]
+    pub book_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = 8 + OpenOrders::INIT_SPACE,
+        seeds = [b"open_orders", market.key().as_ref(), user.key().as_ref()],
+        bump,
+    )]
+    pub open_orders: Box<Account<'info, OpenOrders>>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", market.key().as_ref()],
+        bump,
+    )]
+    pub market_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Source of SPL collateral for bids. Not needed for native SOL markets.
+    #[account(mut)]
+    pub user_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(address = market.collateral_mint)]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(address = market.outcome_mint(outcome) @ ErrorCode::InvalidOutcomeMint)]
+    pub outcome_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// Source of outcome tokens for asks.
+    #[account(
+        mut,
+        token::mint = outcome_mint,
+        token::authority = user,
+    )]
+    pub user_outcome_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Holding checked against a token-gated market's access policy.
+    pub gate_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> PlaceOrder<'info> {
+    /// `remaining_accounts` starts with `max_fills` open-orders accounts, one
+    /// per resting order the new order will fill, in matching order. Any
+    /// accounts after them are collateral transfer hook accounts.
+    #[allow(clippy::too_many_arguments)]
+    pub fn place_order(
+        &mut self,
+        outcome: bool,
+        side: Side,
+        price: u64,
+        quantity: u64,
+        max_fills: u8,
+        proof: &[[u8; 32]],
+        remaining_accounts: &'info [AccountInfo<'info>],
+        bumps: &PlaceOrderBumps,
+    ) -> Result<()> {
+        require!(!self.config.paused, ErrorCode::ProtocolPaused);
+        require!(!self.market.resolved, ErrorCode::MarketResolved);
+        let clock = Clock::get()?;
+        self.market.schedule.require_trading_open(&clock)?;
+        self.market.access_policy.check(
+            &self.user.key(),
+            proof,
+            self.gate_token_account.as_deref(),
+        )?;
+        OrderBook::validate_order(price, quantity)?;
+        require!(
+            remaining_accounts.len() >= max_fills as usize,
+            ErrorCode::InvalidMakerAccount
+        );
+        let (maker_accounts, hook_accounts) = remaining_accounts.split_at(max_fills as usize);
+
+        self.open_orders
+            .open(self.market.key(), self.user.key(), bumps.open_orders);
+
+        // Lock what the whole order could need, topping up the free balance
+        // from the wallet when it falls short
+        match side {
+            Side::Bid => {
+                let cost = OrderBook::notional(price, quantity)?;
+                let shortfall = cost.saturating_sub(self.open_orders.collateral_free);
+                if shortfall > 0 {
+                    let user_token_account = self
+                        .user_token_account
+                        .as_ref()
+                        .map(|account| account.to_account_info());
+                    let received = collateral::deposit(
+                        &self.token_program.to_account_info(),
+                        &self.system_program.to_account_info(),
+                        &self.mint,
+                        &mut self.market_vault,
+                        &self.user.to_account_info(),
+                        user_token_account.as_ref(),
+                        hook_accounts,
+                        shortfall,
+                    )?;
+                    self.open_orders.credit_collateral(received)?;
+                    self.market.book_collateral = self.market.book_collateral.checked_add(received).ok_or(ErrorCode::MathOverflow)?;
+                }
+                self.open_orders.lock_collateral(cost)?;
+            }
+            Side::Ask => {
+                let shortfall = quantity.saturating_sub(self.open_orders.outcome_free(outcome));
+                if shortfall > 0 {
+                    let user_outcome_account = self
+                        .user_outcome_account
+                        .as_ref()
+                        .ok_or(ErrorCode::InsufficientBalance)?;
+                    token_interface::transfer_checked(
+                        CpiContext::new(
+                            self.token_program.to_account_info(),
+                            TransferChecked {
+                                from: user_outcome_account.to_account_info(),
+                                mint: self.outcome_mint.to_account_info(),
+                                to: self.book_vault.to_account_info(),
+                                authority: self.user.to_account_info(),
+                            },
+                        ),
+                        shortfall,
+                        self.outcome_mint.decimals,
+                    )?;
+                    self.open_orders.credit_outcome(outcome, shortfall)?;
+                }
+                self.open_orders.lock_outcome(outcome, quantity)?;
+            }
+        }
+
+        let market_key = self.market.key();
+        let taker = self.open_orders.key();
+        let mut book = self.order_book.load_mut()?;
+        let order_id = book.take_order_id();
+        let (fills, unfilled) = book.match_order(side, price, quantity, &taker, maker_accounts.len())?;
+
+        for (fill, maker_info) in fills.iter().zip(maker_accounts) {
+            require_keys_eq!(maker_info.key(), fill.maker, ErrorCode::InvalidMakerAccount);
+            let mut maker = Account::<OpenOrders>::try_from(maker_info)?;
+            self.open_orders.settle_fill(&mut maker, side, outcome, price, fill)?;
+            maker.exit(&crate::ID)?;
+
+            emit!(OrderFilled {
+                market: market_key,
+                outcome,
+                maker_order_id: fill.maker_order_id,
+                taker_order_id: order_id,
+                maker: maker.owner,
+                taker: self.user.key(),
+                taker_side: side,
+                price: fill.price,
+                quantity: fill.quantity,
+            });
+        }
+
+        if unfilled > 0 {
+            book.insert(
+                side,
+                Order {
+                    order_id,
+                    open_orders: taker,
+                    price,
+                    quantity: unfilled,
+                    placed_at: clock.unix_timestamp,
+                },
+            )?;
+        }
+
+        emit!(OrderPlaced {
+            market: market_key,
+            outcome,
+            order_id,
+            owner: self.user.key(),
+            side,
+            price,
+            quantity,
+            resting: unfilled,
+        });
+
+        Ok(())
+    }
+}
+
+#[event]
+pub struct OrderPlaced {
+    pub market: Pubkey,
+    pub outcome: bool,
+    pub order_id: u64,
+    pub owner: Pubkey,
+    pub side: Side,
+    pub price: u64,
+    pub quantity: u64,
+    /// Quantity left on the book after matching.
+    pub resting: u64,
+}
+
+#[event]
+pub struct OrderFilled {
+    pub market: Pubkey,
+    pub outcome: bool,
+    pub maker_order_id: u64,
+    pub taker_order_id: u64,
+    pub maker: Pubkey,
+    pub taker: Pubkey,
+    pub taker_side: Side,
+    pub price: u64,
+    pub quantity: u64,
+}
@@ -0,0 +1,73 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::Mint;
+
+use crate::utils::collateral;
+use crate::{ErrorCode, Market, PricingEngine, ProtocolConfig};
+
+#[derive(Accounts)]
+pub struct QuoteBet<'info> {
+    pub market: Box<Account<'info, Market>>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Box<Account<'info, ProtocolConfig>>,
+
+    #[account(address = market.collateral_mint)]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+}
+
+impl<'info> QuoteBet<'info> {
+    /// Works out what `place_bet` would do with `amount` right now without
+    /// changing any state. Per-user checks (access policy, exposure limits)
+    /// are not applied.
+    pub fn quote_bet(&self, amount: u64, prediction: bool) -> Result<BetQuote> {
+        require!(!self.config.paused, ErrorCode::ProtocolPaused);
+        require!(!self.market.resolved, ErrorCode::MarketResolved);
+        require!(
+            self.market.pricing_engine == PricingEngine::Parimutuel,
+            ErrorCode::WrongPricingEngine
+        );
+        self.market.schedule.require_trading_open(&Clock::get()?)?;
+        require!(amount > 0, ErrorCode::InvalidAmount);
+
+        let transfer_fee = collateral::transfer_fee(&self.mint, amount)?;
+        let shares_out = amount - transfer_fee;
+        require!(shares_out > 0, ErrorCode::InvalidAmount);
+
+        let mut market = Market::clone(&self.market);
+        let yes_odds_before = market.yes_odds()?;
+        let yes_odds_after = market.add_bet(prediction, shares_out)?;
+
+        market.outcome = Some(prediction);
+        let potential_payout = if prediction {
+            market.settlement_value(shares_out, 0)?
+        } else {
+            market.settlement_value(0, shares_out)?
+        };
+
+        Ok(BetQuote {
+            shares_out,
+            transfer_fee,
+            yes_odds_before,
+            yes_odds_after,
+            price_impact: yes_odds_after.abs_diff(yes_odds_before),
+            potential_payout,
+        })
+    }
+}
+
+/// Result of `quote_bet`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BetQuote {
+    /// Outcome tokens minted, one per unit of collateral the vault receives.
+    pub shares_out: u64,
+    /// Withheld by a transfer-fee collateral mint.
+    pub transfer_fee: u64,
+    /// YES odds in basis points before and after the bet.
+    pub yes_odds_before: u64,
+    pub yes_odds_after: u64,
+    /// How far the bet moves the odds, in basis points.
+    pub price_impact: u64,
+    /// Collateral the tokens would claim if the outcome wins and no other
+    /// bets follow.
+    pub potential_payout: u64,
+}
@@ -0,0 +1,97 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::Mint;
+
+use crate::state::*;
+use crate::utils::collateral;
+use crate::{ErrorCode, Market, PoolTrade, ProtocolConfig};
+
+#[derive(Accounts)]
+pub struct QuoteBuy<'info> {
+    pub market: Box<Account<'info, Market>>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Box<Account<'info, ProtocolConfig>>,
+
+    #[account(
+        seeds = [b"pool", market.key().as_ref()],
+        bump = pool.bump,
+        has_one = market,
+    )]
+    pub pool: Box<Account<'info, LiquidityPool>>,
+
+    #[account(address = market.collateral_mint)]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+}
+
+impl<'info> QuoteBuy<'info> {
+    /// Works out what `buy_shares` would do with `amount` right now by
+    /// running the trade on a copy of the pool. Per-user checks (access
+    /// policy, exposure limits) are not applied.
+    pub fn quote_buy(&self, amount: u64, buy_yes: bool) -> Result<BuyQuote> {
+        require!(!self.config.paused, ErrorCode::ProtocolPaused);
+        require!(!self.market.resolved, ErrorCode::MarketResolved);
+        let clock = Clock::get()?;
+        self.market.schedule.require_trading_open(&clock)?;
+        require!(amount > 0, ErrorCode::InvalidAmount);
+
+        let transfer_fee = collateral::transfer_fee(&self.mint, amount)?;
+        let received = amount - transfer_fee;
+
+        let mut market = Market::clone(&self.market);
+        let mut pool = LiquidityPool::clone(&self.pool);
+        let trade = market.buy_from_pool(
+            &mut pool,
+            received,
+            buy_yes,
+            clock.unix_timestamp,
+            self.config.protocol_fee_bps,
+        )?;
+
+        BuyQuote::new(&trade, transfer_fee, market, buy_yes)
+    }
+}
+
+/// Result of `quote_buy` and `quote_lmsr_buy`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BuyQuote {
+    pub shares_out: u64,
+    /// Trading fee taken from the collateral, at `fee_rate` basis points.
+    pub fee: u64,
+    pub fee_rate: u16,
+    /// Withheld by a transfer-fee collateral mint before the trade.
+    pub transfer_fee: u64,
+    /// Pool YES price in basis points before and after the trade.
+    pub yes_price_before: u64,
+    pub yes_price_after: u64,
+    /// How far the trade moves the price, in basis points.
+    pub price_impact: u64,
+    /// Collateral the shares would claim if their outcome wins, at the
+    /// market totals right after the trade.
+    pub potential_payout: u64,
+}
+
+impl BuyQuote {
+    /// Builds the quote from a trade run on a copy of `market`, which holds
+    /// the totals right after it.
+    pub fn new(trade: &PoolTrade, transfer_fee: u64, mut market: Market, buy_yes: bool) -> Result<Self> {
+        // The bought tokens claim their share of the collateral if their
+        // outcome wins
+        market.outcome = Some(buy_yes);
+        let potential_payout = if buy_yes {
+            market.settlement_value(trade.amount_out, 0)?
+        } else {
+            market.settlement_value(0, trade.amount_out)?
+        };
+
+        Ok(BuyQuote {
+            shares_out: trade.amount_out,
+            fee: trade.fee,
+            fee_rate: trade.fee_rate,
+            transfer_fee,
+            yes_price_before: trade.yes_price_before,
+            yes_price_after: trade.yes_price_after,
+            price_impact: trade.price_impact(),
+            potential_payout,
+        })
+    }
+}
@@ -0,0 +1,69 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::Mint;
+
+use crate::instructions::{BuyQuote, SellQuote};
+use crate::state::*;
+use crate::utils::collateral;
+use crate::{ErrorCode, Market, PricingEngine, ProtocolConfig};
+
+#[derive(Accounts)]
+pub struct QuoteLmsr<'info> {
+    pub market: Box<Account<'info, Market>>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Box<Account<'info, ProtocolConfig>>,
+
+    #[account(
+        seeds = [b"lmsr", market.key().as_ref()],
+        bump = lmsr_pool.bump,
+        has_one = market,
+    )]
+    pub lmsr_pool: Box<Account<'info, LmsrPool>>,
+
+    #[account(address = market.collateral_mint)]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+}
+
+impl<'info> QuoteLmsr<'info> {
+    fn require_open(&self) -> Result<i64> {
+        require!(!self.config.paused, ErrorCode::ProtocolPaused);
+        require!(!self.market.resolved, ErrorCode::MarketResolved);
+        require!(
+            self.market.pricing_engine == PricingEngine::Lmsr,
+            ErrorCode::WrongPricingEngine
+        );
+        let clock = Clock::get()?;
+        self.market.schedule.require_trading_open(&clock)?;
+        Ok(clock.unix_timestamp)
+    }
+
+    /// Works out what `buy_lmsr_shares` would do with `amount` right now by
+    /// running the trade on a copy of the maker. Per-user checks (access
+    /// policy, exposure limits) are not applied.
+    pub fn quote_lmsr_buy(&self, amount: u64, buy_yes: bool) -> Result<BuyQuote> {
+        self.require_open()?;
+        require!(amount > 0, ErrorCode::InvalidAmount);
+
+        let transfer_fee = collateral::transfer_fee(&self.mint, amount)?;
+        let received = amount - transfer_fee;
+
+        let mut market = Market::clone(&self.market);
+        let mut lmsr_pool = LmsrPool::clone(&self.lmsr_pool);
+        let trade = market.buy_from_lmsr(&mut lmsr_pool, received, buy_yes)?;
+
+        BuyQuote::new(&trade, transfer_fee, market, buy_yes)
+    }
+
+    /// Works out what `sell_lmsr_shares` would pay for `shares` right now
+    /// by running the trade on a copy of the maker.
+    pub fn quote_lmsr_sell(&self, shares: u64, sell_yes: bool) -> Result<SellQuote> {
+        self.require_open()?;
+
+        let mut market = Market::clone(&self.market);
+        let mut lmsr_pool = LmsrPool::clone(&self.lmsr_pool);
+        let trade = market.sell_to_lmsr(&mut lmsr_pool, shares, sell_yes)?;
+        let transfer_fee = collateral::transfer_fee(&self.mint, trade.amount_out)?;
+
+        Ok(SellQuote::new(&trade, transfer_fee))
+    }
+}
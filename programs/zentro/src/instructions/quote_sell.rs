@@ -0,0 +1,80 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::Mint;
+
+use crate::state::*;
+use crate::utils::collateral;
+use crate::{ErrorCode, Market, PoolTrade, ProtocolConfig};
+
+#[derive(Accounts)]
+pub struct QuoteSell<'info> {
+    pub market: Box<Account<'info, Market>>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Box<Account<'info, ProtocolConfig>>,
+
+    #[account(
+        seeds = [b"pool", market.key().as_ref()],
+        bump = pool.bump,
+        has_one = market,
+    )]
+    pub pool: Box<Account<'info, LiquidityPool>>,
+
+    #[account(address = market.collateral_mint)]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+}
+
+impl<'info> QuoteSell<'info> {
+    /// Works out what `sell_shares` would pay for `shares` right now by
+    /// running the trade on a copy of the pool.
+    pub fn quote_sell(&self, shares: u64, sell_yes: bool) -> Result<SellQuote> {
+        require!(!self.config.paused, ErrorCode::ProtocolPaused);
+        require!(!self.market.resolved, ErrorCode::MarketResolved);
+        let clock = Clock::get()?;
+        self.market.schedule.require_trading_open(&clock)?;
+
+        let mut market = Market::clone(&self.market);
+        let mut pool = LiquidityPool::clone(&self.pool);
+        let trade = market.sell_to_pool(
+            &mut pool,
+            shares,
+            sell_yes,
+            clock.unix_timestamp,
+            self.config.protocol_fee_bps,
+        )?;
+        let transfer_fee = collateral::transfer_fee(&self.mint, trade.amount_out)?;
+
+        Ok(SellQuote::new(&trade, transfer_fee))
+    }
+}
+
+/// Result of `quote_sell` and `quote_lmsr_sell`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SellQuote {
+    /// Collateral paid out after the trading fee. `min_amount_out` is
+    /// checked against this.
+    pub collateral_out: u64,
+    /// Trading fee at `fee_rate` basis points.
+    pub fee: u64,
+    pub fee_rate: u16,
+    /// Withheld from `collateral_out` by a transfer-fee collateral mint.
+    pub transfer_fee: u64,
+    /// Pool YES price in basis points before and after the trade.
+    pub yes_price_before: u64,
+    pub yes_price_after: u64,
+    /// How far the trade moves the price, in basis points.
+    pub price_impact: u64,
+}
+
+impl SellQuote {
+    pub fn new(trade: &PoolTrade, transfer_fee: u64) -> Self {
+        SellQuote {
+            collateral_out: trade.amount_out,
+            fee: trade.fee,
+            fee_rate: trade.fee_rate,
+            transfer_fee,
+            yes_price_before: trade.yes_price_before,
+            yes_price_after: trade.yes_price_after,
+            price_impact: trade.price_impact(),
+        }
+    }
+}
@@ -0,0 +1,171 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{self, Burn, Mint, TokenAccount, TokenInterface};
+
+use crate::state::*;
+use crate::utils::collateral;
+use crate::{ErrorCode, Market, MarketVoided};
+
+#[derive(Accounts)]
+pub struct RedeemLp<'info> {
+    #[account(mut)]
+    pub market: Box<Account<'info, Market>>,
+
+    #[account(
+        mut,
+        seeds = [b"pool", market.key().as_ref()],
+        bump = pool.bump,
+        has_one = market,
+    )]
+    pub pool: Box<Account<'info, LiquidityPool>>,
+
+    #[account(
+        mut,
+        seeds = [b"liquidity", market.key().as_ref(), user.key().as_ref()],
+        bump = liquidity_position.bump,
+    )]
+    pub liquidity_position: Box<Account<'info, LiquidityPosition>>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", market.key().as_ref()],
+        bump,
+    )]
+    pub market_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub user_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(address = market.collateral_mint)]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(mut, address = market.yes_mint @ ErrorCode::InvalidOutcomeMint)]
+    pub yes_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(mut, address = market.no_mint @ ErrorCode::InvalidOutcomeMint)]
+    pub no_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(mut, address = pool.yes_token_vault)]
+    pub pool_yes_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut, address = pool.no_token_vault)]
+    pub pool_no_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut, address = pool.lp_mint @ ErrorCode::InvalidLpMint)]
+    pub lp_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// Staked LP tokens are burned from here.
+    #[account(mut, address = pool.lp_vault)]
+    pub lp_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+impl<'info> RedeemLp<'info> {
+    pub fn redeem_lp(
+        &mut self,
+        shares: u64,
+        hook_accounts: &[AccountInfo<'info>],
+        bumps: &RedeemLpBumps,
+    ) -> Result<()> {
+        require!(shares > 0, ErrorCode::InvalidAmount);
+        require!(shares <= self.liquidity_position.shares, ErrorCode::InsufficientLiquidity);
+        require!(self.market.is_settled(&Clock::get()?), ErrorCode::MarketNotResolved);
+
+        // Past the deadline without a resolution, the market is void
+        if !self.market.resolved && !self.market.voided {
+            self.market.voided = true;
+            emit!(MarketVoided {
+                market: self.market.key(),
+            });
+        }
+
+        // Settle fees earned on the old stake before it shrinks
+        self.liquidity_position
+            .unstake(self.pool.fee_per_share, shares)?;
+        self.pool.unstake(shares)?;
+        let lp_supply = self.lp_mint.supply;
+        let (yes_amount, no_amount) = self.pool.remove_liquidity(shares, lp_supply)?;
+
+        let pool_seeds = self.pool.signer_seeds();
+        token_interface::burn(
+            CpiContext::new_with_signer(
+                self.token_program.to_account_info(),
+                Burn {
+                    mint: self.lp_mint.to_account_info(),
+                    from: self.lp_vault.to_account_info(),
+                    authority: self.pool.to_account_info(),
+                },
+                &[&pool_seeds[..]],
+            ),
+            shares,
+        )?;
+
+        // The provider's whole share of inventory is burned; only what the
+        // market still pays out for it becomes collateral
+        let payout = self.market.settlement_value(yes_amount, no_amount)?;
+
+        for (mint, pool_vault, amount) in [
+            (&self.yes_mint, &self.pool_yes_vault, yes_amount),
+            (&self.no_mint, &self.pool_no_vault, no_amount),
+        ] {
+            if amount == 0 {
+                continue;
+            }
+            token_interface::burn(
+                CpiContext::new_with_signer(
+                    self.token_program.to_account_info(),
+                    Burn {
+                        mint: mint.to_account_info(),
+                        from: pool_vault.to_account_info(),
+                        authority: self.pool.to_account_info(),
+                    },
+                    &[&pool_seeds[..]],
+                ),
+                amount,
+            )?;
+        }
+
+        self.market.total_claimed = self
+            .market
+            .total_claimed
+            .checked_add(payout)
+            .ok_or(ErrorCode::MathOverflow)?;
+
+        let market_key = self.market.key();
+        if payout > 0 {
+            let seeds = &[b"vault", market_key.as_ref(), &[bumps.market_vault]];
+            collateral::withdraw(
+                &self.token_program.to_account_info(),
+                &self.market_vault,
+                &self.mint,
+                &self.user.to_account_info(),
+                &self.user_token_account.to_account_info(),
+                hook_accounts,
+                payout,
+                &[&seeds[..]],
+            )?;
+        }
+
+        emit!(LiquidityRedeemedEvent {
+            market: market_key,
+            user: self.user.key(),
+            shares,
+            amount: payout,
+            total_liquidity: lp_supply - shares,
+        });
+
+        Ok(())
+    }
+}
+
+#[event]
+pub struct LiquidityRedeemedEvent {
+    pub market: Pubkey,
+    pub user: Pubkey,
+    pub shares: u64,
+    pub amount: u64,
+    pub total_liquidity: u64,
+}
@@ -0,0 +1,224 @@
+use anchor_lang::prelude::*;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token_interface::{self, Burn, Mint, TokenAccount, TokenInterface, TransferChecked};
+
+use crate::state::*;
+use crate::utils::collateral;
+use crate::{ErrorCode, Market};
+
+#[derive(Accounts)]
+pub struct RemoveLiquidity<'info> {
+    #[account(mut)]
+    pub market: Box<Account<'info, Market>>,
+
+    #[account(
+        mut,
+        seeds = [b"pool", market.key().as_ref()],
+        bump = pool.bump,
+        has_one = market,
+    )]
+    pub pool: Box<Account<'info, LiquidityPool>>,
+
+    #[account(
+        mut,
+        seeds = [b"liquidity", market.key().as_ref(), user.key().as_ref()],
+        bump = liquidity_position.bump,
+    )]
+    pub liquidity_position: Box<Account<'info, LiquidityPosition>>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", market.key().as_ref()],
+        bump,
+    )]
+    pub market_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub user_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(address = market.collateral_mint)]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(mut, address = market.yes_mint @ ErrorCode::InvalidOutcomeMint)]
+    pub yes_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(mut, address = market.no_mint @ ErrorCode::InvalidOutcomeMint)]
+    pub no_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(mut, address = pool.yes_token_vault)]
+    pub pool_yes_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut, address = pool.no_token_vault)]
+    pub pool_no_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut, address = pool.lp_mint @ ErrorCode::InvalidLpMint)]
+    pub lp_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// Staked LP tokens are burned from here.
+    #[account(mut, address = pool.lp_vault)]
+    pub lp_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Receives the YES surplus when the pool is not balanced.
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = yes_mint,
+        associated_token::authority = user,
+        associated_token::token_program = token_program,
+    )]
+    pub user_yes_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Receives the NO surplus when the pool is not balanced.
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = no_mint,
+        associated_token::authority = user,
+        associated_token::token_program = token_program,
+    )]
+    pub user_no_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> RemoveLiquidity<'info> {
+    pub fn remove_liquidity(
+        &mut self,
+        shares: u64,
+        min_amount_out: u64,
+        hook_accounts: &[AccountInfo<'info>],
+        bumps: &RemoveLiquidityBumps,
+    ) -> Result<()> {
+        require!(shares > 0, ErrorCode::InvalidAmount);
+        require!(shares <= self.liquidity_position.shares, ErrorCode::InsufficientLiquidity);
+        // Settled markets redeem at fixed ratios, so their totals can't change
+        let clock = Clock::get()?;
+        require!(!self.market.is_settled(&clock), ErrorCode::MarketSettled);
+
+        // Settle fees earned on the old stake before it shrinks
+        self.liquidity_position
+            .unstake(self.pool.fee_per_share, shares)?;
+        self.pool.unstake(shares)?;
+        let lp_supply = self.lp_mint.supply;
+        self.pool.accumulate_price(clock.unix_timestamp)?;
+        let (yes_amount, no_amount) = self.pool.remove_liquidity(shares, lp_supply)?;
+
+        let pool_seeds = self.pool.signer_seeds();
+        token_interface::burn(
+            CpiContext::new_with_signer(
+                self.token_program.to_account_info(),
+                Burn {
+                    mint: self.lp_mint.to_account_info(),
+                    from: self.lp_vault.to_account_info(),
+                    authority: self.pool.to_account_info(),
+                },
+                &[&pool_seeds[..]],
+            ),
+            shares,
+        )?;
+
+        // Matched YES and NO merge back into collateral
+        let merged = yes_amount.min(no_amount);
+        require!(merged >= min_amount_out, ErrorCode::SlippageExceeded);
+
+        let pool_signer = &[&pool_seeds[..]];
+        for (mint, pool_vault, user_account, amount) in [
+            (&self.yes_mint, &self.pool_yes_vault, &self.user_yes_account, yes_amount),
+            (&self.no_mint, &self.pool_no_vault, &self.user_no_account, no_amount),
+        ] {
+            if merged > 0 {
+                token_interface::burn(
+                    CpiContext::new_with_signer(
+                        self.token_program.to_account_info(),
+                        Burn {
+                            mint: mint.to_account_info(),
+                            from: pool_vault.to_account_info(),
+                            authority: self.pool.to_account_info(),
+                        },
+                        pool_signer,
+                    ),
+                    merged,
+                )?;
+            }
+
+            let surplus = amount - merged;
+            if surplus > 0 {
+                token_interface::transfer_checked(
+                    CpiContext::new_with_signer(
+                        self.token_program.to_account_info(),
+                        TransferChecked {
+                            from: pool_vault.to_account_info(),
+                            mint: mint.to_account_info(),
+                            to: user_account.to_account_info(),
+                            authority: self.pool.to_account_info(),
+                        },
+                        pool_signer,
+                    ),
+                    surplus,
+                    mint.decimals,
+                )?;
+            }
+        }
+
+        self.market.total_yes_tokens = self
+            .market
+            .total_yes_tokens
+            .checked_sub(merged)
+            .ok_or(ErrorCode::MathOverflow)?;
+        self.market.total_no_tokens = self
+            .market
+            .total_no_tokens
+            .checked_sub(merged)
+            .ok_or(ErrorCode::MathOverflow)?;
+        self.market.total_collateral = self
+            .market
+            .total_collateral
+            .checked_sub(merged)
+            .ok_or(ErrorCode::MathOverflow)?;
+
+        let market_key = self.market.key();
+        if merged > 0 {
+            let seeds = &[b"vault", market_key.as_ref(), &[bumps.market_vault]];
+            collateral::withdraw(
+                &self.token_program.to_account_info(),
+                &self.market_vault,
+                &self.mint,
+                &self.user.to_account_info(),
+                &self.user_token_account.to_account_info(),
+                hook_accounts,
+                merged,
+                &[&seeds[..]],
+            )?;
+        }
+
+        emit!(LiquidityRemovedEvent {
+            market: market_key,
+            user: self.user.key(),
+            shares,
+            amount: merged,
+            yes_tokens: yes_amount - merged,
+            no_tokens: no_amount - merged,
+            total_liquidity: lp_supply - shares,
+        });
+
+        Ok(())
+    }
+}
+
+#[event]
+pub struct LiquidityRemovedEvent {
+    pub market: Pubkey,
+    pub user: Pubkey,
+    pub shares: u64,
+    /// Collateral returned from merged complete sets.
+    pub amount: u64,
+    /// Surplus outcome tokens returned alongside the collateral.
+    pub yes_tokens: u64,
+    pub no_tokens: u64,
+    pub total_liquidity: u64,
+}
@@ -0,0 +1,135 @@
+use anchor_lang::prelude::*;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token_interface::{self, Mint, TokenAccount, TokenInterface, TransferChecked};
+
+use crate::state::*;
+use crate::utils::collateral;
+use crate::{ErrorCode, Market};
+
+#[derive(Accounts)]
+#[instruction(outcome: bool)]
+pub struct SettleFunds<'info> {
+    #[account(mut)]
+    pub market: Box<Account<'info, Market>>,
+
+    #[account(
+        seeds = [b"order_book", market.key().as_ref(), &[outcome as u8]],
+        bump = order_book.load()?.bump,
+        has_one = market,
+    )]
+    pub order_book: AccountLoader<'info, OrderBook>,
+
+    #[account(mut, address = order_book.load()?.token_vault)]
+    pub book_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        seeds = [b"open_orders", market.key().as_ref(), user.key().as_ref()],
+        bump = open_orders.bump,
+    )]
+    pub open_orders: Box<Account<'info, OpenOrders>>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", market.key().as_ref()],
+        bump,
+    )]
+    pub market_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub user_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(address = market.collateral_mint)]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(address = market.outcome_mint(outcome) @ ErrorCode::InvalidOutcomeMint)]
+    pub outcome_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = outcome_mint,
+        associated_token::authority = user,
+        associated_token::token_program = token_program,
+    )]
+    pub user_outcome_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> SettleFunds<'info> {
+    /// Withdraws the free collateral and the free tokens of this book's
+    /// outcome. Locked balances stay behind resting orders.
+    pub fn settle_funds(
+        &mut self,
+        outcome: bool,
+        hook_accounts: &[AccountInfo<'info>],
+        bumps: &SettleFundsBumps,
+    ) -> Result<()> {
+        let (collateral_amount, token_amount) = self.open_orders.take_free(outcome);
+
+        let market_key = self.market.key();
+        if collateral_amount > 0 {
+            self.market.book_collateral = self.market.book_collateral.checked_sub(collateral_amount).ok_or(ErrorCode::MathOverflow)?;
+            let seeds = &[b"vault", market_key.as_ref(), &[bumps.market_vault]];
+            collateral::withdraw(
+                &self.token_program.to_account_info(),
+                &self.market_vault,
+                &self.mint,
+                &self.user.to_account_info(),
+                &self.user_token_account.to_account_info(),
+                hook_accounts,
+                collateral_amount,
+                &[&seeds[..]],
+            )?;
+        }
+
+        if token_amount > 0 {
+            let book_bump = self.order_book.load()?.bump;
+            let seeds = &[
+                b"order_book",
+                market_key.as_ref(),
+                &[outcome as u8],
+                &[book_bump],
+            ];
+            token_interface::transfer_checked(
+                CpiContext::new_with_signer(
+                    self.token_program.to_account_info(),
+                    TransferChecked {
+                        from: self.book_vault.to_account_info(),
+                        mint: self.outcome_mint.to_account_info(),
+                        to: self.user_outcome_account.to_account_info(),
+                        authority: self.order_book.to_account_info(),
+                    },
+                    &[&seeds[..]],
+                ),
+                token_amount,
+                self.outcome_mint.decimals,
+            )?;
+        }
+
+        emit!(FundsSettled {
+            market: market_key,
+            owner: self.user.key(),
+            outcome,
+            collateral: collateral_amount,
+            tokens: token_amount,
+        });
+
+        Ok(())
+    }
+}
+
+#[event]
+pub struct FundsSettled {
+    pub market: Pubkey,
+    pub owner: Pubkey,
+    pub outcome: bool,
+    pub collateral: u64,
+    pub tokens: u64,
+}
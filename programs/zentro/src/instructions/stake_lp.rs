@@ -0,0 +1,91 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{self, Mint, TokenAccount, TokenInterface, TransferChecked};
+
+use crate::state::*;
+use crate::{ErrorCode, Market};
+
+#[derive(Accounts)]
+pub struct StakeLp<'info> {
+    pub market: Box<Account<'info, Market>>,
+
+    #[account(
+        mut,
+        seeds = [b"pool", market.key().as_ref()],
+        bump = pool.bump,
+        has_one = market,
+    )]
+    pub pool: Box<Account<'info, LiquidityPool>>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = 8 + LiquidityPosition::INIT_SPACE,
+        seeds = [b"liquidity", market.key().as_ref(), user.key().as_ref()],
+        bump,
+    )]
+    pub liquidity_position: Box<Account<'info, LiquidityPosition>>,
+
+    #[account(address = pool.lp_mint @ ErrorCode::InvalidLpMint)]
+    pub lp_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(mut, address = pool.lp_vault)]
+    pub lp_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        token::mint = lp_mint,
+        token::authority = user,
+    )]
+    pub user_lp_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> StakeLp<'info> {
+    /// Moves LP tokens from the user's wallet into the pool's LP vault,
+    /// where they earn a share of trading fees and can be removed.
+    pub fn stake_lp(&mut self, amount: u64, bumps: &StakeLpBumps) -> Result<()> {
+        require!(amount > 0, ErrorCode::InvalidAmount);
+
+        self.liquidity_position
+            .open(self.market.key(), self.user.key(), bumps.liquidity_position);
+        self.liquidity_position.stake(self.pool.fee_per_share, amount)?;
+        self.pool.stake(amount)?;
+
+        token_interface::transfer_checked(
+            CpiContext::new(
+                self.token_program.to_account_info(),
+                TransferChecked {
+                    from: self.user_lp_account.to_account_info(),
+                    mint: self.lp_mint.to_account_info(),
+                    to: self.lp_vault.to_account_info(),
+                    authority: self.user.to_account_info(),
+                },
+            ),
+            amount,
+            self.lp_mint.decimals,
+        )?;
+
+        emit!(LpStaked {
+            market: self.market.key(),
+            user: self.user.key(),
+            amount,
+            staked: self.liquidity_position.shares,
+        });
+
+        Ok(())
+    }
+}
+
+#[event]
+pub struct LpStaked {
+    pub market: Pubkey,
+    pub user: Pubkey,
+    pub amount: u64,
+    /// The user's stake afterwards.
+    pub staked: u64,
+}
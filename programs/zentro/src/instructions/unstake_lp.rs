@@ -0,0 +1,94 @@
+use anchor_lang::prelude::*;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token_interface::{self, Mint, TokenAccount, TokenInterface, TransferChecked};
+
+use crate::state::*;
+use crate::{ErrorCode, Market};
+
+#[derive(Accounts)]
+pub struct UnstakeLp<'info> {
+    pub market: Box<Account<'info, Market>>,
+
+    #[account(
+        mut,
+        seeds = [b"pool", market.key().as_ref()],
+        bump = pool.bump,
+        has_one = market,
+    )]
+    pub pool: Box<Account<'info, LiquidityPool>>,
+
+    #[account(
+        mut,
+        seeds = [b"liquidity", market.key().as_ref(), user.key().as_ref()],
+        bump = liquidity_position.bump,
+    )]
+    pub liquidity_position: Box<Account<'info, LiquidityPosition>>,
+
+    #[account(address = pool.lp_mint @ ErrorCode::InvalidLpMint)]
+    pub lp_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(mut, address = pool.lp_vault)]
+    pub lp_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = lp_mint,
+        associated_token::authority = user,
+        associated_token::token_program = token_program,
+    )]
+    pub user_lp_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> UnstakeLp<'info> {
+    /// Returns staked LP tokens to the user's wallet, where they can be
+    /// transferred but no longer earn fees. Fees earned so far stay
+    /// claimable.
+    pub fn unstake_lp(&mut self, amount: u64) -> Result<()> {
+        require!(amount > 0, ErrorCode::InvalidAmount);
+
+        self.liquidity_position.unstake(self.pool.fee_per_share, amount)?;
+        self.pool.unstake(amount)?;
+
+        let pool_seeds = self.pool.signer_seeds();
+        token_interface::transfer_checked(
+            CpiContext::new_with_signer(
+                self.token_program.to_account_info(),
+                TransferChecked {
+                    from: self.lp_vault.to_account_info(),
+                    mint: self.lp_mint.to_account_info(),
+                    to: self.user_lp_account.to_account_info(),
+                    authority: self.pool.to_account_info(),
+                },
+                &[&pool_seeds[..]],
+            ),
+            amount,
+            self.lp_mint.decimals,
+        )?;
+
+        emit!(LpUnstaked {
+            market: self.market.key(),
+            user: self.user.key(),
+            amount,
+            staked: self.liquidity_position.shares,
+        });
+
+        Ok(())
+    }
+}
+
+#[event]
+pub struct LpUnstaked {
+    pub market: Pubkey,
+    pub user: Pubkey,
+    pub amount: u64,
+    /// The user's stake afterwards.
+    pub staked: u64,
+}
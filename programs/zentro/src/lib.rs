@@ -1,6 +1,19 @@
 ```rust
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+use anchor_lang::solana_program::hash::hashv;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::metadata::Metadata;
+use anchor_spl::token_interface::{self, Burn, Mint, MintTo, TokenAccount, TokenInterface, TransferChecked};
+
+pub mod instructions;
+pub mod state;
+pub mod utils;
+
+pub use instructions::*;
+use state::{FeeCurve, LiquidityPool, LiquidityPoolParams, LmsrPool, PriceHistory, Side, SignedOrder};
+use utils::fixed_point::{mul_div, mul_div_u64, Rounding, BPS};
+use utils::{collateral, lmsr};
+use utils::metadata::{self as token_metadata, MetadataAccounts};
 
 declare_id!("11111111111111111111111111111112");
 
@@ -8,69 +21,166 @@ declare_id!("11111111111111111111111111111112");
 pub mod zentro {
     use super::*;
 
-    pub fn initialize_market(
-        ctx: Context<InitializeMarket>,
-        market_id: u64,
-        question: String,
-        end_time: i64,
-        oracle: Pubkey,
-    ) -> Result<()> {
+    pub fn initialize_market(ctx: Context<InitializeMarket>, params: InitializeMarketParams) -> Result<()> {
+        let InitializeMarketParams {
+            market_id,
+            question_hash,
+            question,
+            end_time,
+            oracle,
+            access_policy,
+            exposure_limits,
+            schedule,
+            max_price_impact_bps,
+        } = params;
+        require!(question.len() <= 200, ErrorCode::QuestionTooLong);
+        schedule.validate(end_time)?;
+        require!(exposure_limits.max_user_share_bps as u64 <= BPS, ErrorCode::InvalidPercentage);
+        require!(max_price_impact_bps as u64 <= BPS, ErrorCode::InvalidPercentage);
+        require!(
+            question_hash == Market::question_hash(&question, end_time, &oracle, &ctx.accounts.mint.key()),
+            ErrorCode::InvalidQuestionHash
+        );
+
         let market = &mut ctx.accounts.market;
         market.id = market_id;
         market.question = question;
+        market.question_hash = question_hash;
         market.end_time = end_time;
         market.oracle = oracle;
+        market.collateral_mint = ctx.accounts.mint.key();
+        market.yes_mint = ctx.accounts.yes_mint.key();
+        market.no_mint = ctx.accounts.no_mint.key();
         market.total_yes_tokens = 0;
         market.total_no_tokens = 0;
+        market.total_collateral = 0;
+        market.total_claimed = 0;
+        market.book_collateral = 0;
         market.resolved = false;
         market.outcome = None;
         market.creator = ctx.accounts.creator.key();
+        market.access_policy = access_policy;
+        market.exposure_limits = exposure_limits;
+        market.schedule = schedule;
+        market.max_price_impact_bps = max_price_impact_bps;
+        market.voided = false;
+        market.pricing_engine = PricingEngine::Parimutuel;
+        market.has_price_history = false;
+        market.bump = ctx.bumps.market;
+
+        // Name the outcome mints so wallets can display positions
+        let seeds = market.signer_seeds();
+        let signer = &[&seeds[..]];
+        let metadata_accounts = MetadataAccounts {
+            metadata_program: &ctx.accounts.token_metadata_program.to_account_info(),
+            authority: &market.to_account_info(),
+            payer: &ctx.accounts.creator.to_account_info(),
+            system_program: &ctx.accounts.system_program.to_account_info(),
+            rent: &ctx.accounts.rent.to_account_info(),
+        };
+        let base_uri = &ctx.accounts.config.metadata_base_uri;
+        for (metadata, mint, suffix) in [
+            (&ctx.accounts.yes_metadata, &ctx.accounts.yes_mint, "YES"),
+            (&ctx.accounts.no_metadata, &ctx.accounts.no_mint, "NO"),
+        ] {
+            token_metadata::create_mint_metadata(
+                &metadata_accounts,
+                &metadata.to_account_info(),
+                &mint.to_account_info(),
+                token_metadata::mint_name(&market.key(), suffix),
+                format!("Z{}", suffix),
+                token_metadata::mint_uri(base_uri, &market.key(), suffix),
+                signer,
+            )?;
+        }
         
         Ok(())
     }
 
-    pub fn place_bet(
-        ctx: Context<PlaceBet>,
+    pub fn place_bet<'info>(
+        ctx: Context<'_, '_, '_, 'info, PlaceBet<'info>>,
         amount: u64,
         prediction: bool,
+        max_price: u64,
+        proof: Vec<[u8; 32]>,
     ) -> Result<()> {
         let market = &mut ctx.accounts.market;
         let user_position = &mut ctx.accounts.user_position;
         
+        require!(!ctx.accounts.config.paused, ErrorCode::ProtocolPaused);
+        market.check_price_history(ctx.accounts.price_history.is_some())?;
+        market.access_policy.check(
+            &ctx.accounts.user.key(),
+            &proof,
+            ctx.accounts.gate_token_account.as_deref(),
+        )?;
         require!(!market.resolved, ErrorCode::MarketResolved);
-        require!(Clock::get()?.unix_timestamp < market.end_time, ErrorCode::MarketExpired);
+        require!(
+            market.pricing_engine == PricingEngine::Parimutuel,
+            ErrorCode::WrongPricingEngine
+        );
+        let clock = Clock::get()?;
+        market.schedule.require_trading_open(&clock)?;
         require!(amount > 0, ErrorCode::InvalidAmount);
 
-        // Transfer tokens from user to market vault
-        let cpi_accounts = Transfer {
-            from: ctx.accounts.user_token_account.to_account_info(),
-            to: ctx.accounts.market_vault.to_account_info(),
-            authority: ctx.accounts.user.to_account_info(),
-        };
-        let cpi_program = ctx.accounts.token_program.to_account_info();
-        let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
-        token::transfer(cpi_ctx, amount)?;
+        // Move collateral into the market vault, crediting only what
+        // arrived after any transfer fee
+        let user_token_account = ctx
+            .accounts
+            .user_token_account
+            .as_ref()
+            .map(|account| account.to_account_info());
+        let received = collateral::deposit(
+            &ctx.accounts.token_program.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            &ctx.accounts.mint,
+            &mut ctx.accounts.market_vault,
+            &ctx.accounts.user.to_account_info(),
+            user_token_account.as_ref(),
+            ctx.remaining_accounts,
+            amount,
+        )?;
+        require!(received > 0, ErrorCode::InvalidAmount);
 
-        // Update market totals
-        if prediction {
-            market.total_yes_tokens += amount;
-        } else {
-            market.total_no_tokens += amount;
-        }
+        // Limits apply to what the bet is credited with, which is less than
+        // `amount` for a transfer-fee mint
+        let user_staked = market.check_exposure(user_position.total_staked, received)?;
+
+        // Mint outcome tokens one-for-one with the collateral credited
+        let seeds = market.signer_seeds();
+        let signer = &[&seeds[..]];
+        token_interface::mint_to(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                MintTo {
+                    mint: ctx.accounts.outcome_mint.to_account_info(),
+                    to: ctx.accounts.user_outcome_account.to_account_info(),
+                    authority: market.to_account_info(),
+                },
+                signer,
+            ),
+            received,
+        )?;
+
+        let yes_odds = market.add_bet(prediction, received)?;
+        let odds = if prediction { yes_odds } else { BPS - yes_odds };
+        require!(odds <= max_price, ErrorCode::PriceLimitExceeded);
 
         // Update user position
         user_position.user = ctx.accounts.user.key();
         user_position.market = market.key();
-        if prediction {
-            user_position.yes_tokens += amount;
-        } else {
-            user_position.no_tokens += amount;
+        user_position.total_staked = user_staked;
+
+        if let Some(price_history) = &ctx.accounts.price_history {
+            price_history
+                .load_mut()?
+                .record(clock.unix_timestamp, market.yes_odds()?, received)?;
         }
 
         emit!(BetPlaced {
             market: market.key(),
             user: ctx.accounts.user.key(),
-            amount,
+            amount: received,
             prediction,
         });
 
@@ -85,7 +195,12 @@ pub mod zentro {
         
         require!(!market.resolved, ErrorCode::MarketAlreadyResolved);
         require!(ctx.accounts.oracle.key() == market.oracle, ErrorCode::UnauthorizedOracle);
-        require!(Clock::get()?.unix_timestamp >= market.end_time, ErrorCode::MarketNotExpired);
+        let clock = Clock::get()?;
+        require!(clock.unix_timestamp >= market.end_time, ErrorCode::MarketNotExpired);
+        require!(
+            !market.schedule.resolution_deadline.has_passed(&clock),
+            ErrorCode::ResolutionDeadlinePassed
+        );
 
         market.resolved = true;
         market.outcome = Some(outcome);
@@ -98,20 +213,14 @@ pub mod zentro {
         Ok(())
     }
 
-    pub fn claim_winnings(ctx: Context<ClaimWinnings>) -> Result<()> {
-        let market = &ctx.accounts.market;
-        let user_position = &ctx.accounts.user_position;
+    /// Burns the user's winning outcome tokens for their share of the pool.
+    pub fn claim_winnings<'info>(ctx: Context<'_, '_, '_, 'info, ClaimWinnings<'info>>) -> Result<()> {
+        let market = &mut ctx.accounts.market;
         
         require!(market.resolved, ErrorCode::MarketNotResolved);
-        require!(user_position.user == ctx.accounts.user.key(), ErrorCode::UnauthorizedUser);
-        require!(!user_position.claimed, ErrorCode::AlreadyClaimed);
 
         let outcome = market.outcome.unwrap();
-        let winning_tokens = if outcome {
-            user_position.yes_tokens
-        } else {
-            user_position.no_tokens
-        };
+        let winning_tokens = ctx.accounts.user_outcome_account.amount;
 
         require!(winning_tokens > 0, ErrorCode::NoWinnings);
 
@@ -121,199 +230,3452 @@ pub mod zentro {
             market.total_no_tokens
         };
 
-        let total_pool = market.total_yes_tokens + market.total_no_tokens;
-        let payout = (winning_tokens as u128 * total_pool as u128 / total_winning_pool as u128) as u64;
+        // Totals are not reduced by burns, so every holder redeems at the
+        // same ratio no matter when they claim.
+        let payout = mul_div_u64(winning_tokens, market.redemption_pot(), total_winning_pool, Rounding::Down)?;
+
+        token_interface::burn(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Burn {
+                    mint: ctx.accounts.winning_mint.to_account_info(),
+                    from: ctx.accounts.user_outcome_account.to_account_info(),
+                    authority: ctx.accounts.user.to_account_info(),
+                },
+            ),
+            winning_tokens,
+        )?;
 
         // Transfer winnings to user
+        let market_key = market.key();
         let seeds = &[
-            b"market",
-            &market.id.to_le_bytes(),
+            b"vault",
+            market_key.as_ref(),
             &[ctx.bumps.market_vault],
         ];
         let signer = &[&seeds[..]];
 
-        let cpi_accounts = Transfer {
-            from: ctx.accounts.market_vault.to_account_info(),
-            to: ctx.accounts.user_token_account.to_account_info(),
-            authority: ctx.accounts.market_vault.to_account_info(),
-        };
-        let cpi_program = ctx.accounts.token_program.to_account_info();
-        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
-        token::transfer(cpi_ctx, payout)?;
+        collateral::withdraw(
+            &ctx.accounts.token_program.to_account_info(),
+            &ctx.accounts.market_vault,
+            &ctx.accounts.mint,
+            &ctx.accounts.user.to_account_info(),
+            &ctx.accounts.user_token_account.to_account_info(),
+            ctx.remaining_accounts,
+            payout,
+            signer,
+        )?;
 
-        let user_position = &mut ctx.accounts.user_position;
-        user_position.claimed = true;
+        market.total_claimed = market.total_claimed.checked_add(payout).ok_or(ErrorCode::MathOverflow)?;
 
         emit!(WinningsClaimed {
             market: market.key(),
             user: ctx.accounts.user.key(),
             amount: payout,
+            tokens_burned: winning_tokens,
         });
 
         Ok(())
     }
-}
 
-#[derive(Accounts)]
-#[instruction(market_id: u64)]
-pub struct InitializeMarket<'info> {
-    #[account(
-        init,
-        payer = creator,
-        space = 8 + Market::INIT_SPACE,
-        seeds = [b"market", market_id.to_le_bytes().as_ref()],
-        bump
-    )]
-    pub market: Account<'info, Market>,
-    
-    #[account(
-        init,
-        payer = creator,
-        token::mint = mint,
-        token::authority = market_vault,
-        seeds = [b"vault", market.key().as_ref()],
-        bump
-    )]
-    pub market_vault: Account<'info, TokenAccount>,
-    
-    #[account(mut)]
-    pub creator: Signer<'info>,
-    
-    pub mint: Account<'info, token::Mint>,
-    pub token_program: Program<'info, Token>,
-    pub system_program: Program<'info, System>,
-    pub rent: Sysvar<'info, Rent>,
-}
+    /// Burns a user's YES and NO tokens for their stake once a market has
+    /// passed its resolution deadline without being resolved. The first
+    /// refund marks it voided.
+    pub fn claim_refund<'info>(ctx: Context<'_, '_, '_, 'info, ClaimRefund<'info>>) -> Result<()> {
+        let market = &mut ctx.accounts.market;
 
-#[derive(Accounts)]
-pub struct PlaceBet<'info> {
-    #[account(mut)]
-    pub market: Account<'info, Market>,
-    
-    #[account(
-        init_if_needed,
-        payer = user,
-        space = 8 + UserPosition::INIT_SPACE,
-        seeds = [b"position", user.key().as_ref(), market.key().as_ref()],
-        bump
-    )]
-    pub user_position: Account<'info, UserPosition>,
-    
-    #[account(mut)]
-    pub market_vault: Account<'info, TokenAccount>,
-    
-    #[account(mut)]
-    pub user_token_account: Account<'info, TokenAccount>,
-    
-    #[account(mut)]
-    pub user: Signer<'info>,
-    
-    pub token_program: Program<'info, Token>,
-    pub system_program: Program<'info, System>,
-}
+        require!(!market.resolved, ErrorCode::MarketAlreadyResolved);
+        require!(
+            market.schedule.resolution_deadline.has_passed(&Clock::get()?),
+            ErrorCode::ResolutionDeadlineNotReached
+        );
 
-#[derive(Accounts)]
-pub struct ResolveMarket<'info> {
-    #[account(mut)]
-    pub market: Account<'info, Market>,
-    
-    pub oracle: Signer<'info>,
-}
+        if !market.voided {
+            market.voided = true;
+            emit!(MarketVoided {
+                market: market.key(),
+            });
+        }
 
-#[derive(Accounts)]
-pub struct ClaimWinnings<'info> {
-    pub market: Account<'info, Market>,
-    
-    #[account(
-        mut,
-        seeds = [b"position", user.key().as_ref(), market.key().as_ref()],
-        bump
-    )]
-    pub user_position: Account<'info, UserPosition>,
-    
-    #[account(
-        mut,
-        seeds = [b"vault", market.key().as_ref()],
-        bump
-    )]
-    pub market_vault: Account<'info, TokenAccount>,
-    
-    #[account(mut)]
-    pub user_token_account: Account<'info, TokenAccount>,
-    
-    #[account(mut)]
-    pub user: Signer<'info>,
-    
-    pub token_program: Program<'info, Token>,
-}
+        let yes_tokens = ctx.accounts.user_yes_account.amount;
+        let no_tokens = ctx.accounts.user_no_account.amount;
+        let tokens = yes_tokens.checked_add(no_tokens).ok_or(ErrorCode::MathOverflow)?;
+        require!(tokens > 0, ErrorCode::NoWinnings);
 
-#[account]
-#[derive(InitSpace)]
-pub struct Market {
-    pub id: u64,
-    #[max_len(200)]
-    pub question: String,
-    pub end_time: i64,
-    pub oracle: Pubkey,
-    pub total_yes_tokens: u64,
-    pub total_no_tokens: u64,
-    pub resolved: bool,
-    pub outcome: Option<bool>,
-    pub creator: Pubkey,
-}
+        // Every outstanding token refunds at the same rate, which is one
+        // collateral unit per token unless complete sets were minted.
+        let total_tokens = market.total_yes_tokens as u128 + market.total_no_tokens as u128;
+        let refund = u64::try_from(mul_div(
+            tokens as u128,
+            market.total_collateral as u128,
+            total_tokens,
+            Rounding::Down,
+        )?)
+        .map_err(|_| ErrorCode::MathOverflow)?;
 
-#[account]
-#[derive(InitSpace)]
-pub struct UserPosition {
-    pub user: Pubkey,
-    pub market: Pubkey,
-    pub yes_tokens: u64,
-    pub no_tokens: u64,
-    pub claimed: bool,
-}
+        for (mint, from, tokens) in [
+            (&ctx.accounts.yes_mint, &ctx.accounts.user_yes_account, yes_tokens),
+            (&ctx.accounts.no_mint, &ctx.accounts.user_no_account, no_tokens),
+        ] {
+            if tokens == 0 {
+                continue;
+            }
+            token_interface::burn(
+                CpiContext::new(
+                    ctx.accounts.token_program.to_account_info(),
+                    Burn {
+                        mint: mint.to_account_info(),
+                        from: from.to_account_info(),
+                        authority: ctx.accounts.user.to_account_info(),
+                    },
+                ),
+                tokens,
+            )?;
+        }
 
-#[event]
-pub struct BetPlaced {
-    pub market: Pubkey,
-    pub user: Pubkey,
-    pub amount: u64,
-    pub prediction: bool,
-}
+        let market_key = market.key();
+        let seeds = &[
+            b"vault",
+            market_key.as_ref(),
+            &[ctx.bumps.market_vault],
+        ];
+        let signer = &[&seeds[..]];
 
-#[event]
-pub struct MarketResolved {
-    pub market: Pubkey,
-    pub outcome: bool,
-}
+        collateral::withdraw(
+            &ctx.accounts.token_program.to_account_info(),
+            &ctx.accounts.market_vault,
+            &ctx.accounts.mint,
+            &ctx.accounts.user.to_account_info(),
+            &ctx.accounts.user_token_account.to_account_info(),
+            ctx.remaining_accounts,
+            refund,
+            signer,
+        )?;
 
-#[event]
-pub struct WinningsClaimed {
-    pub market: Pubkey,
-    pub user: Pubkey,
-    pub amount: u64,
-}
+        market.total_claimed = market.total_claimed.checked_add(refund).ok_or(ErrorCode::MathOverflow)?;
 
-#[error_code]
-pub enum ErrorCode {
-    #[msg("Market has already been resolved")]
-    MarketResolved,
-    #[msg("Market has expired")]
-    MarketExpired,
-    #[msg("Invalid bet amount")]
-    InvalidAmount,
-    #[msg("Market already resolved")]
-    MarketAlreadyResolved,
-    #[msg("Unauthorized oracle")]
-    UnauthorizedOracle,
-    #[msg("Market not expired yet")]
-    MarketNotExpired,
-    #[msg("Market not resolved")]
-    MarketNotResolved,
-    #[msg("Unauthorized user")]
-    UnauthorizedUser,
-    #[msg("Already claimed")]
-    AlreadyClaimed,
-    #[msg("No winnings to claim")]
-    NoWinnings,
+        emit!(RefundClaimed {
+            market: market_key,
+            user: ctx.accounts.user.key(),
+            amount: refund,
+        });
+
+        Ok(())
+    }
+
+    /// Deposits collateral for an equal amount of YES and NO tokens.
+    pub fn mint_complete_set<'info>(
+        ctx: Context<'_, '_, '_, 'info, MintCompleteSet<'info>>,
+        amount: u64,
+        proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        let market = &mut ctx.accounts.market;
+
+        require!(!ctx.accounts.config.paused, ErrorCode::ProtocolPaused);
+        market.access_policy.check(
+            &ctx.accounts.user.key(),
+            &proof,
+            ctx.accounts.gate_token_account.as_deref(),
+        )?;
+        require!(!market.resolved, ErrorCode::MarketResolved);
+        market.schedule.require_trading_open(&Clock::get()?)?;
+        require!(amount > 0, ErrorCode::InvalidAmount);
+
+        let user_token_account = ctx
+            .accounts
+            .user_token_account
+            .as_ref()
+            .map(|account| account.to_account_info());
+        let received = collateral::deposit(
+            &ctx.accounts.token_program.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            &ctx.accounts.mint,
+            &mut ctx.accounts.market_vault,
+            &ctx.accounts.user.to_account_info(),
+            user_token_account.as_ref(),
+            ctx.remaining_accounts,
+            amount,
+        )?;
+        require!(received > 0, ErrorCode::InvalidAmount);
+
+        let seeds = market.signer_seeds();
+        let signer = &[&seeds[..]];
+        for (mint, to) in [
+            (&ctx.accounts.yes_mint, &ctx.accounts.user_yes_account),
+            (&ctx.accounts.no_mint, &ctx.accounts.user_no_account),
+        ] {
+            token_interface::mint_to(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    MintTo {
+                        mint: mint.to_account_info(),
+                        to: to.to_account_info(),
+                        authority: market.to_account_info(),
+                    },
+                    signer,
+                ),
+                received,
+            )?;
+        }
+
+        market.total_yes_tokens = market.total_yes_tokens.checked_add(received).ok_or(ErrorCode::MathOverflow)?;
+        market.total_no_tokens = market.total_no_tokens.checked_add(received).ok_or(ErrorCode::MathOverflow)?;
+        market.total_collateral = market.total_collateral.checked_add(received).ok_or(ErrorCode::MathOverflow)?;
+
+        emit!(CompleteSetMinted {
+            market: market.key(),
+            user: ctx.accounts.user.key(),
+            amount: received,
+        });
+
+        Ok(())
+    }
+
+    /// Burns an equal amount of YES and NO tokens for the same amount of
+    /// collateral. Allowed at any time, including after settlement.
+    pub fn merge_complete_set<'info>(
+        ctx: Context<'_, '_, '_, 'info, MergeCompleteSet<'info>>,
+        amount: u64,
+    ) -> Result<()> {
+        let market = &mut ctx.accounts.market;
+
+        require!(amount > 0, ErrorCode::InvalidAmount);
+
+        for (mint, from) in [
+            (&ctx.accounts.yes_mint, &ctx.accounts.user_yes_account),
+            (&ctx.accounts.no_mint, &ctx.accounts.user_no_account),
+        ] {
+            token_interface::burn(
+                CpiContext::new(
+                    ctx.accounts.token_program.to_account_info(),
+                    Burn {
+                        mint: mint.to_account_info(),
+                        from: from.to_account_info(),
+                        authority: ctx.accounts.user.to_account_info(),
+                    },
+                ),
+                amount,
+            )?;
+        }
+
+        // Claims and refunds redeem at ratios fixed by the totals, so once
+        // the market is settled they are left alone. A pair is worth at
+        // least one collateral unit in either case, so paying exactly one
+        // keeps the vault solvent.
+        if !market.is_settled(&Clock::get()?) {
+            market.total_yes_tokens = market.total_yes_tokens.checked_sub(amount).ok_or(ErrorCode::MathOverflow)?;
+            market.total_no_tokens = market.total_no_tokens.checked_sub(amount).ok_or(ErrorCode::MathOverflow)?;
+            market.total_collateral = market.total_collateral.checked_sub(amount).ok_or(ErrorCode::MathOverflow)?;
+        } else {
+            market.total_claimed = market.total_claimed.checked_add(amount).ok_or(ErrorCode::MathOverflow)?;
+        }
+
+        let market_key = market.key();
+        let seeds = &[
+            b"vault",
+            market_key.as_ref(),
+            &[ctx.bumps.market_vault],
+        ];
+        let signer = &[&seeds[..]];
+
+        collateral::withdraw(
+            &ctx.accounts.token_program.to_account_info(),
+            &ctx.accounts.market_vault,
+            &ctx.accounts.mint,
+            &ctx.accounts.user.to_account_info(),
+            &ctx.accounts.user_token_account.to_account_info(),
+            ctx.remaining_accounts,
+            amount,
+            signer,
+        )?;
+
+        emit!(CompleteSetMerged {
+            market: market_key,
+            user: ctx.accounts.user.key(),
+            amount,
+        });
+
+        Ok(())
+    }
+
+    /// Moves YES and NO tokens to another wallet together with the matching
+    /// share of the sender's recorded stake, so exposure limits follow the
+    /// position. The recipient must pass the market's access policy.
+    pub fn transfer_position(
+        ctx: Context<TransferPosition>,
+        yes_amount: u64,
+        no_amount: u64,
+        proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        let market = &ctx.accounts.market;
+        let recipient = ctx.accounts.recipient.key();
+
+        require!(recipient != ctx.accounts.user.key(), ErrorCode::InvalidRecipient);
+        let moved = yes_amount.checked_add(no_amount).ok_or(ErrorCode::MathOverflow)?;
+        require!(moved > 0, ErrorCode::InvalidAmount);
+        market.access_policy.check(
+            &recipient,
+            &proof,
+            ctx.accounts.recipient_gate_token_account.as_deref(),
+        )?;
+
+        for (mint, from, to, amount) in [
+            (&ctx.accounts.yes_mint, &ctx.accounts.user_yes_account, &ctx.accounts.recipient_yes_account, yes_amount),
+            (&ctx.accounts.no_mint, &ctx.accounts.user_no_account, &ctx.accounts.recipient_no_account, no_amount),
+        ] {
+            if amount == 0 {
+                continue;
+            }
+            token_interface::transfer_checked(
+                CpiContext::new(
+                    ctx.accounts.token_program.to_account_info(),
+                    TransferChecked {
+                        from: from.to_account_info(),
+                        mint: mint.to_account_info(),
+                        to: to.to_account_info(),
+                        authority: ctx.accounts.user.to_account_info(),
+                    },
+                ),
+                amount,
+                mint.decimals,
+            )?;
+        }
+
+        // Tokens can also arrive through plain SPL transfers, so the stake
+        // on record may be smaller than what is being moved.
+        let user_position = &mut ctx.accounts.user_position;
+        let moved_stake = moved.min(user_position.total_staked);
+        user_position.total_staked -= moved_stake;
+
+        let recipient_position = &mut ctx.accounts.recipient_position;
+        recipient_position.user = recipient;
+        recipient_position.market = market.key();
+        recipient_position.total_staked = recipient_position
+            .total_staked
+            .checked_add(moved_stake)
+            .ok_or(ErrorCode::MathOverflow)?;
+
+        let open_interest = market
+            .total_yes_tokens
+            .checked_add(market.total_no_tokens)
+            .ok_or(ErrorCode::MathOverflow)?;
+        market.exposure_limits.check(recipient_position.total_staked, open_interest)?;
+
+        emit!(PositionTransferred {
+            market: market.key(),
+            from: ctx.accounts.user.key(),
+            to: recipient,
+            yes_amount,
+            no_amount,
+        });
+
+        Ok(())
+    }
+
+    /// Read-only check that the vault covers everything the market still
+    /// owes to token holders, traders and fee recipients. The report is
+    /// returned as return data, and a `SolvencyViolation` event is emitted
+    /// when an invariant fails.
+    pub fn audit_market(ctx: Context<AuditMarket>) -> Result<AuditReport> {
+        let market = &ctx.accounts.market;
+
+        // Fees are left in the vault outside total_collateral until claimed
+        let mut unpaid_fees = 0u64;
+        if !ctx.accounts.pool.data_is_empty() {
+            let pool = LiquidityPool::try_deserialize(&mut &ctx.accounts.pool.try_borrow_data()?[..])?;
+            unpaid_fees = unpaid_fees
+                .checked_add(pool.accumulated_fees)
+                .and_then(|fees| fees.checked_add(pool.protocol_fees))
+                .ok_or(ErrorCode::MathOverflow)?;
+        }
+        if !ctx.accounts.lmsr_pool.data_is_empty() {
+            let lmsr_pool = LmsrPool::try_deserialize(&mut &ctx.accounts.lmsr_pool.try_borrow_data()?[..])?;
+            unpaid_fees = unpaid_fees.checked_add(lmsr_pool.accumulated_fees).ok_or(ErrorCode::MathOverflow)?;
+            if !lmsr_pool.surplus_claimed {
+                unpaid_fees = unpaid_fees.checked_add(market.lmsr_surplus()).ok_or(ErrorCode::MathOverflow)?;
+            }
+        }
+
+        let report = AuditReport::new(
+            market,
+            ctx.accounts.market_vault.amount,
+            ctx.accounts.yes_mint.supply,
+            ctx.accounts.no_mint.supply,
+            market.schedule.resolution_deadline.has_passed(&Clock::get()?),
+            unpaid_fees,
+        )?;
+
+        if !report.solvent || !report.supply_matches {
+            emit!(SolvencyViolation {
+                market: market.key(),
+                report,
+            });
+        }
+
+        Ok(report)
+    }
+
+    pub fn initialize_pool(ctx: Context<InitializePool>, fee_rate: u16) -> Result<()> {
+        let market = &mut ctx.accounts.market;
+        require!(!market.resolved, ErrorCode::MarketResolved);
+        require!(
+            market.pricing_engine == PricingEngine::Parimutuel,
+            ErrorCode::PricingEngineAlreadySet
+        );
+        // Bets would otherwise share the pool's complete sets at settlement
+        require!(
+            market.total_yes_tokens == 0 && market.total_no_tokens == 0,
+            ErrorCode::MarketHasBets
+        );
+        market.pricing_engine = PricingEngine::ConstantProduct;
+
+        ctx.accounts.pool.initialize(
+            LiquidityPoolParams {
+                authority: ctx.accounts.creator.key(),
+                market: market.key(),
+                token_mint: market.collateral_mint,
+                token_vault: ctx.accounts.market_vault.key(),
+                yes_token_mint: market.yes_mint,
+                no_token_mint: market.no_mint,
+                yes_token_vault: ctx.accounts.pool_yes_vault.key(),
+                no_token_vault: ctx.accounts.pool_no_vault.key(),
+                lp_mint: ctx.accounts.lp_mint.key(),
+                lp_vault: ctx.accounts.lp_vault.key(),
+                fee_rate,
+            },
+            ctx.bumps.pool,
+        )?;
+
+        // Name the LP mint so wallets can display liquidity positions
+        let pool = &ctx.accounts.pool;
+        let pool_seeds = pool.signer_seeds();
+        let metadata_accounts = MetadataAccounts {
+            metadata_program: &ctx.accounts.token_metadata_program.to_account_info(),
+            authority: &pool.to_account_info(),
+            payer: &ctx.accounts.creator.to_account_info(),
+            system_program: &ctx.accounts.system_program.to_account_info(),
+            rent: &ctx.accounts.rent.to_account_info(),
+        };
+        token_metadata::create_mint_metadata(
+            &metadata_accounts,
+            &ctx.accounts.lp_metadata.to_account_info(),
+            &ctx.accounts.lp_mint.to_account_info(),
+            token_metadata::mint_name(&market.key(), "LP"),
+            "ZLP".to_string(),
+            token_metadata::mint_uri(&ctx.accounts.config.metadata_base_uri, &market.key(), "LP"),
+            &[&pool_seeds[..]],
+        )
+    }
+
+    pub fn add_liquidity<'info>(
+        ctx: Context<'_, '_, '_, 'info, AddLiquidity<'info>>,
+        amount: u64,
+        proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        ctx.accounts.add_liquidity(amount, &proof, ctx.remaining_accounts, &ctx.bumps)
+    }
+
+    /// Returns liquidity before the market settles: matched YES and NO are
+    /// merged back into collateral and any surplus side is sent as tokens.
+    pub fn remove_liquidity<'info>(
+        ctx: Context<'_, '_, '_, 'info, RemoveLiquidity<'info>>,
+        shares: u64,
+        min_amount_out: u64,
+    ) -> Result<()> {
+        ctx.accounts.remove_liquidity(shares, min_amount_out, ctx.remaining_accounts, &ctx.bumps)
+    }
+
+    /// Redeems liquidity after settlement for the collateral its share of
+    /// the pool's inventory is worth.
+    pub fn redeem_lp<'info>(ctx: Context<'_, '_, '_, 'info, RedeemLp<'info>>, shares: u64) -> Result<()> {
+        ctx.accounts.redeem_lp(shares, ctx.remaining_accounts, &ctx.bumps)
+    }
+
+    /// Pays a liquidity provider the pool fees their staked shares have
+    /// earned.
+    pub fn claim_lp_fees<'info>(ctx: Context<'_, '_, '_, 'info, ClaimLpFees<'info>>) -> Result<()> {
+        ctx.accounts.claim_lp_fees(ctx.remaining_accounts, &ctx.bumps)
+    }
+
+    /// Pays the market creator the LMSR maker's trading fees and, once the
+    /// market resolves, whatever subsidy the winners did not need.
+    pub fn claim_lmsr_funds<'info>(ctx: Context<'_, '_, '_, 'info, ClaimLmsrFunds<'info>>) -> Result<()> {
+        ctx.accounts.claim_lmsr_funds(ctx.remaining_accounts, &ctx.bumps)
+    }
+
+    /// Stakes LP tokens from the user's wallet so they earn trading fees.
+    pub fn stake_lp(ctx: Context<StakeLp>, amount: u64) -> Result<()> {
+        ctx.accounts.stake_lp(amount, &ctx.bumps)
+    }
+
+    /// Moves staked LP tokens back to the user's wallet.
+    pub fn unstake_lp(ctx: Context<UnstakeLp>, amount: u64) -> Result<()> {
+        ctx.accounts.unstake_lp(amount)
+    }
+
+    /// Buys YES or NO shares from the market's pool with collateral. Fails
+    /// if the bought outcome's price would end above `max_price` basis
+    /// points.
+    pub fn buy_shares<'info>(
+        ctx: Context<'_, '_, '_, 'info, BuyShares<'info>>,
+        amount: u64,
+        buy_yes: bool,
+        min_amount_out: u64,
+        max_price: u64,
+        proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        let market = &mut ctx.accounts.market;
+        let pool = &mut ctx.accounts.pool;
+        let user_position = &mut ctx.accounts.user_position;
+
+        require!(!ctx.accounts.config.paused, ErrorCode::ProtocolPaused);
+        market.check_price_history(ctx.accounts.price_history.is_some())?;
+        market.access_policy.check(
+            &ctx.accounts.user.key(),
+            &proof,
+            ctx.accounts.gate_token_account.as_deref(),
+        )?;
+        require!(!market.resolved, ErrorCode::MarketResolved);
+        let clock = Clock::get()?;
+        market.schedule.require_trading_open(&clock)?;
+        require!(amount > 0, ErrorCode::InvalidAmount);
+
+        let user_token_account = ctx
+            .accounts
+            .user_token_account
+            .as_ref()
+            .map(|account| account.to_account_info());
+        let received = collateral::deposit(
+            &ctx.accounts.token_program.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            &ctx.accounts.mint,
+            &mut ctx.accounts.market_vault,
+            &ctx.accounts.user.to_account_info(),
+            user_token_account.as_ref(),
+            ctx.remaining_accounts,
+            amount,
+        )?;
+
+        let user_staked = market.check_exposure(user_position.total_staked, received)?;
+
+        let PoolTrade { amount_out: shares_out, fee, fee_rate, .. } = market.buy_from_pool(
+            pool,
+            received,
+            buy_yes,
+            clock.unix_timestamp,
+            ctx.accounts.config.protocol_fee_bps,
+        )?;
+        require!(shares_out >= min_amount_out, ErrorCode::SlippageExceeded);
+        require!(pool.get_price(buy_yes)? <= max_price, ErrorCode::PriceLimitExceeded);
+        let net_amount = received - fee;
+
+        // The collateral after fees becomes complete sets in the pool,
+        // which then pays the bought side out to the user
+        let seeds = market.signer_seeds();
+        let signer = &[&seeds[..]];
+        for (mint, to) in [
+            (&ctx.accounts.yes_mint, &ctx.accounts.pool_yes_vault),
+            (&ctx.accounts.no_mint, &ctx.accounts.pool_no_vault),
+        ] {
+            token_interface::mint_to(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    MintTo {
+                        mint: mint.to_account_info(),
+                        to: to.to_account_info(),
+                        authority: market.to_account_info(),
+                    },
+                    signer,
+                ),
+                net_amount,
+            )?;
+        }
+
+        let (outcome_mint, pool_vault, user_outcome_account) = if buy_yes {
+            (&ctx.accounts.yes_mint, &ctx.accounts.pool_yes_vault, &ctx.accounts.user_yes_account)
+        } else {
+            (&ctx.accounts.no_mint, &ctx.accounts.pool_no_vault, &ctx.accounts.user_no_account)
+        };
+        let pool_seeds = pool.signer_seeds();
+        token_interface::transfer_checked(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: pool_vault.to_account_info(),
+                    mint: outcome_mint.to_account_info(),
+                    to: user_outcome_account.to_account_info(),
+                    authority: pool.to_account_info(),
+                },
+                &[&pool_seeds[..]],
+            ),
+            shares_out,
+            outcome_mint.decimals,
+        )?;
+
+        user_position.user = ctx.accounts.user.key();
+        user_position.market = market.key();
+        user_position.total_staked = user_staked;
+
+        if let Some(price_history) = &ctx.accounts.price_history {
+            price_history
+                .load_mut()?
+                .record(clock.unix_timestamp, pool.get_price(true)?, received)?;
+        }
+
+        emit!(SharesTraded {
+            market: market.key(),
+            user: ctx.accounts.user.key(),
+            is_buy: true,
+            outcome: buy_yes,
+            collateral_amount: received,
+            shares: shares_out,
+            fee,
+            fee_rate,
+            yes_price: pool.get_price(true)?,
+        });
+
+        Ok(())
+    }
+
+    /// Sells YES or NO shares back to the market's pool for collateral.
+    /// Fails if the sold outcome's price would end below `min_price` basis
+    /// points.
+    pub fn sell_shares<'info>(
+        ctx: Context<'_, '_, '_, 'info, SellShares<'info>>,
+        shares: u64,
+        sell_yes: bool,
+        min_amount_out: u64,
+        min_price: u64,
+        proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        let market = &mut ctx.accounts.market;
+        let pool = &mut ctx.accounts.pool;
+        let user_position = &mut ctx.accounts.user_position;
+
+        require!(!ctx.accounts.config.paused, ErrorCode::ProtocolPaused);
+        market.check_price_history(ctx.accounts.price_history.is_some())?;
+        market.access_policy.check(
+            &ctx.accounts.user.key(),
+            &proof,
+            ctx.accounts.gate_token_account.as_deref(),
+        )?;
+        require!(!market.resolved, ErrorCode::MarketResolved);
+        let clock = Clock::get()?;
+        market.schedule.require_trading_open(&clock)?;
+
+        let PoolTrade { amount_out: collateral_out, fee, fee_rate, .. } = market.sell_to_pool(
+            pool,
+            shares,
+            sell_yes,
+            clock.unix_timestamp,
+            ctx.accounts.config.protocol_fee_bps,
+        )?;
+        require!(collateral_out >= min_amount_out, ErrorCode::SlippageExceeded);
+        require!(pool.get_price(sell_yes)? >= min_price, ErrorCode::PriceLimitExceeded);
+        let merged = collateral_out + fee;
+
+        // Selling frees the stake the shares were bought with
+        let holdings = ctx.accounts.user_yes_account.amount + ctx.accounts.user_no_account.amount;
+        user_position.user = ctx.accounts.user.key();
+        user_position.market = market.key();
+        user_position.release_stake(shares, holdings)?;
+
+        let (outcome_mint, pool_vault, user_outcome_account) = if sell_yes {
+            (&ctx.accounts.yes_mint, &ctx.accounts.pool_yes_vault, &ctx.accounts.user_yes_account)
+        } else {
+            (&ctx.accounts.no_mint, &ctx.accounts.pool_no_vault, &ctx.accounts.user_no_account)
+        };
+        token_interface::transfer_checked(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: user_outcome_account.to_account_info(),
+                    mint: outcome_mint.to_account_info(),
+                    to: pool_vault.to_account_info(),
+                    authority: ctx.accounts.user.to_account_info(),
+                },
+            ),
+            shares,
+            outcome_mint.decimals,
+        )?;
+
+        // The pool merges complete sets back into collateral
+        let pool_seeds = pool.signer_seeds();
+        for (mint, from) in [
+            (&ctx.accounts.yes_mint, &ctx.accounts.pool_yes_vault),
+            (&ctx.accounts.no_mint, &ctx.accounts.pool_no_vault),
+        ] {
+            token_interface::burn(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    Burn {
+                        mint: mint.to_account_info(),
+                        from: from.to_account_info(),
+                        authority: pool.to_account_info(),
+                    },
+                    &[&pool_seeds[..]],
+                ),
+                merged,
+            )?;
+        }
+
+        let market_key = market.key();
+        let seeds = &[
+            b"vault",
+            market_key.as_ref(),
+            &[ctx.bumps.market_vault],
+        ];
+        let signer = &[&seeds[..]];
+
+        collateral::withdraw(
+            &ctx.accounts.token_program.to_account_info(),
+            &ctx.accounts.market_vault,
+            &ctx.accounts.mint,
+            &ctx.accounts.user.to_account_info(),
+            &ctx.accounts.user_token_account.to_account_info(),
+            ctx.remaining_accounts,
+            collateral_out,
+            signer,
+        )?;
+
+        if let Some(price_history) = &ctx.accounts.price_history {
+            price_history
+                .load_mut()?
+                .record(clock.unix_timestamp, pool.get_price(true)?, merged)?;
+        }
+
+        emit!(SharesTraded {
+            market: market_key,
+            user: ctx.accounts.user.key(),
+            is_buy: false,
+            outcome: sell_yes,
+            collateral_amount: collateral_out,
+            shares,
+            fee,
+            fee_rate,
+            yes_price: pool.get_price(true)?,
+        });
+
+        Ok(())
+    }
+
+    /// Sets or clears the pool's dynamic fee curve. Restricted to fee managers.
+    pub fn set_fee_curve(ctx: Context<SetFeeCurve>, fee_curve: Option<FeeCurve>) -> Result<()> {
+        ctx.accounts.pool.set_fee_curve(fee_curve)?;
+
+        emit!(FeeCurveUpdated {
+            market: ctx.accounts.pool.market,
+            fee_curve,
+        });
+
+        Ok(())
+    }
+
+    /// Attaches an LMSR market maker with liquidity parameter `b`. The
+    /// creator deposits the maker's worst-case loss, `b * ln(2)`, as the
+    /// subsidy.
+    pub fn initialize_lmsr<'info>(
+        ctx: Context<'_, '_, '_, 'info, InitializeLmsr<'info>>,
+        liquidity_parameter: u64,
+        fee_rate: u16,
+    ) -> Result<()> {
+        let market = &mut ctx.accounts.market;
+        require!(!market.resolved, ErrorCode::MarketResolved);
+        require!(
+            market.pricing_engine == PricingEngine::Parimutuel,
+            ErrorCode::PricingEngineAlreadySet
+        );
+        // Bets would otherwise redeem at the maker's one-unit-per-share payout
+        require!(
+            market.total_yes_tokens == 0 && market.total_no_tokens == 0,
+            ErrorCode::MarketHasBets
+        );
+
+        let subsidy = lmsr::max_loss(liquidity_parameter)?;
+        let creator_token_account = ctx
+            .accounts
+            .creator_token_account
+            .as_ref()
+            .map(|account| account.to_account_info());
+        let received = collateral::deposit(
+            &ctx.accounts.token_program.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            &ctx.accounts.mint,
+            &mut ctx.accounts.market_vault,
+            &ctx.accounts.creator.to_account_info(),
+            creator_token_account.as_ref(),
+            ctx.remaining_accounts,
+            subsidy,
+        )?;
+        require!(received >= subsidy, ErrorCode::InsufficientSubsidy);
+
+        // The subsidy backs winning shares alongside trader collateral
+        market.total_collateral = market.total_collateral.checked_add(received).ok_or(ErrorCode::MathOverflow)?;
+        market.pricing_engine = PricingEngine::Lmsr;
+
+        ctx.accounts.lmsr_pool.initialize(
+            market.key(),
+            liquidity_parameter,
+            received,
+            fee_rate,
+            ctx.bumps.lmsr_pool,
+        )
+    }
+
+    /// Buys YES or NO shares from the market's LMSR maker with collateral.
+    /// Fails if the bought outcome's price would end above `max_price`.
+    pub fn buy_lmsr_shares<'info>(
+        ctx: Context<'_, '_, '_, 'info, BuyLmsrShares<'info>>,
+        amount: u64,
+        buy_yes: bool,
+        min_amount_out: u64,
+        max_price: u64,
+        proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        let market = &mut ctx.accounts.market;
+        let lmsr_pool = &mut ctx.accounts.lmsr_pool;
+        let user_position = &mut ctx.accounts.user_position;
+
+        require!(!ctx.accounts.config.paused, ErrorCode::ProtocolPaused);
+        market.check_price_history(ctx.accounts.price_history.is_some())?;
+        market.access_policy.check(
+            &ctx.accounts.user.key(),
+            &proof,
+            ctx.accounts.gate_token_account.as_deref(),
+        )?;
+        require!(!market.resolved, ErrorCode::MarketResolved);
+        require!(market.pricing_engine == PricingEngine::Lmsr, ErrorCode::WrongPricingEngine);
+        let clock = Clock::get()?;
+        market.schedule.require_trading_open(&clock)?;
+        require!(amount > 0, ErrorCode::InvalidAmount);
+
+        let user_token_account = ctx
+            .accounts
+            .user_token_account
+            .as_ref()
+            .map(|account| account.to_account_info());
+        let received = collateral::deposit(
+            &ctx.accounts.token_program.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            &ctx.accounts.mint,
+            &mut ctx.accounts.market_vault,
+            &ctx.accounts.user.to_account_info(),
+            user_token_account.as_ref(),
+            ctx.remaining_accounts,
+            amount,
+        )?;
+
+        let user_staked = market.check_exposure(user_position.total_staked, received)?;
+
+        let PoolTrade { amount_out: shares_out, fee, .. } = market.buy_from_lmsr(lmsr_pool, received, buy_yes)?;
+        require!(shares_out >= min_amount_out, ErrorCode::SlippageExceeded);
+        require!(lmsr_pool.get_price(buy_yes)? <= max_price, ErrorCode::PriceLimitExceeded);
+
+        let seeds = market.signer_seeds();
+        let signer = &[&seeds[..]];
+        token_interface::mint_to(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                MintTo {
+                    mint: ctx.accounts.outcome_mint.to_account_info(),
+                    to: ctx.accounts.user_outcome_account.to_account_info(),
+                    authority: market.to_account_info(),
+                },
+                signer,
+            ),
+            shares_out,
+        )?;
+
+        user_position.user = ctx.accounts.user.key();
+        user_position.market = market.key();
+        user_position.total_staked = user_staked;
+
+        if let Some(price_history) = &ctx.accounts.price_history {
+            price_history
+                .load_mut()?
+                .record(clock.unix_timestamp, lmsr_pool.get_price(true)?, received)?;
+        }
+
+        emit!(SharesTraded {
+            market: market.key(),
+            user: ctx.accounts.user.key(),
+            is_buy: true,
+            outcome: buy_yes,
+            collateral_amount: received,
+            shares: shares_out,
+            fee,
+            fee_rate: lmsr_pool.fee_rate,
+            yes_price: lmsr_pool.get_price(true)?,
+        });
+
+        Ok(())
+    }
+
+    /// Sells YES or NO shares back to the market's LMSR maker. Fails if the
+    /// sold outcome's price would end below `min_price`.
+    pub fn sell_lmsr_shares<'info>(
+        ctx: Context<'_, '_, '_, 'info, SellLmsrShares<'info>>,
+        shares: u64,
+        sell_yes: bool,
+        min_amount_out: u64,
+        min_price: u64,
+        proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        let market = &mut ctx.accounts.market;
+        let lmsr_pool = &mut ctx.accounts.lmsr_pool;
+        let user_position = &mut ctx.accounts.user_position;
+
+        require!(!ctx.accounts.config.paused, ErrorCode::ProtocolPaused);
+        market.check_price_history(ctx.accounts.price_history.is_some())?;
+        market.access_policy.check(
+            &ctx.accounts.user.key(),
+            &proof,
+            ctx.accounts.gate_token_account.as_deref(),
+        )?;
+        require!(!market.resolved, ErrorCode::MarketResolved);
+        require!(market.pricing_engine == PricingEngine::Lmsr, ErrorCode::WrongPricingEngine);
+        let clock = Clock::get()?;
+        market.schedule.require_trading_open(&clock)?;
+
+        let PoolTrade { amount_out: collateral_out, fee, .. } = market.sell_to_lmsr(lmsr_pool, shares, sell_yes)?;
+        require!(collateral_out >= min_amount_out, ErrorCode::SlippageExceeded);
+        require!(lmsr_pool.get_price(sell_yes)? >= min_price, ErrorCode::PriceLimitExceeded);
+        let proceeds = collateral_out + fee;
+
+        // Selling frees the stake the shares were bought with
+        let holdings = ctx.accounts.user_outcome_account.amount + ctx.accounts.user_other_outcome_account.amount;
+        user_position.user = ctx.accounts.user.key();
+        user_position.market = market.key();
+        user_position.release_stake(shares, holdings)?;
+
+        token_interface::burn(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Burn {
+                    mint: ctx.accounts.outcome_mint.to_account_info(),
+                    from: ctx.accounts.user_outcome_account.to_account_info(),
+                    authority: ctx.accounts.user.to_account_info(),
+                },
+            ),
+            shares,
+        )?;
+
+        let market_key = market.key();
+        let seeds = &[
+            b"vault",
+            market_key.as_ref(),
+            &[ctx.bumps.market_vault],
+        ];
+        let signer = &[&seeds[..]];
+
+        collateral::withdraw(
+            &ctx.accounts.token_program.to_account_info(),
+            &ctx.accounts.market_vault,
+            &ctx.accounts.mint,
+            &ctx.accounts.user.to_account_info(),
+            &ctx.accounts.user_token_account.to_account_info(),
+            ctx.remaining_accounts,
+            collateral_out,
+            signer,
+        )?;
+
+        if let Some(price_history) = &ctx.accounts.price_history {
+            price_history
+                .load_mut()?
+                .record(clock.unix_timestamp, lmsr_pool.get_price(true)?, proceeds)?;
+        }
+
+        emit!(SharesTraded {
+            market: market_key,
+            user: ctx.accounts.user.key(),
+            is_buy: false,
+            outcome: sell_yes,
+            collateral_amount: collateral_out,
+            shares,
+            fee,
+            fee_rate: lmsr_pool.fee_rate,
+            yes_price: lmsr_pool.get_price(true)?,
+        });
+
+        Ok(())
+    }
+
+    /// Simulates `place_bet`, returning the quote as return data. Meant to be
+    /// called through `simulateTransaction`.
+    pub fn quote_bet(ctx: Context<QuoteBet>, amount: u64, prediction: bool) -> Result<BetQuote> {
+        ctx.accounts.quote_bet(amount, prediction)
+    }
+
+    /// Simulates `buy_shares`, returning the quote as return data.
+    pub fn quote_buy(ctx: Context<QuoteBuy>, amount: u64, buy_yes: bool) -> Result<BuyQuote> {
+        ctx.accounts.quote_buy(amount, buy_yes)
+    }
+
+    /// Simulates `sell_shares`, returning the quote as return data.
+    pub fn quote_sell(ctx: Context<QuoteSell>, shares: u64, sell_yes: bool) -> Result<SellQuote> {
+        ctx.accounts.quote_sell(shares, sell_yes)
+    }
+
+    /// Simulates `buy_lmsr_shares`, returning the quote as return data.
+    pub fn quote_lmsr_buy(ctx: Context<QuoteLmsr>, amount: u64, buy_yes: bool) -> Result<BuyQuote> {
+        ctx.accounts.quote_lmsr_buy(amount, buy_yes)
+    }
+
+    /// Simulates `sell_lmsr_shares`, returning the quote as return data.
+    pub fn quote_lmsr_sell(ctx: Context<QuoteLmsr>, shares: u64, sell_yes: bool) -> Result<SellQuote> {
+        ctx.accounts.quote_lmsr_sell(shares, sell_yes)
+    }
+
+    /// Starts recording price candles of `interval` seconds for a market.
+    pub fn initialize_price_history(ctx: Context<InitializePriceHistory>, interval: i64) -> Result<()> {
+        ctx.accounts.initialize_price_history(interval, &ctx.bumps)
+    }
+
+    /// Opens the limit order book for one outcome of a market.
+    pub fn initialize_order_book(ctx: Context<InitializeOrderBook>, outcome: bool) -> Result<()> {
+        ctx.accounts.initialize_order_book(outcome, &ctx.bumps)
+    }
+
+    /// Places a limit order for `quantity` outcome shares at `price` basis
+    /// points. It fills against crossing resting orders at their prices and
+    /// the rest joins the book.
+    pub fn place_order<'info>(
+        ctx: Context<'_, '_, 'info, 'info, PlaceOrder<'info>>,
+        outcome: bool,
+        side: Side,
+        price: u64,
+        quantity: u64,
+        max_fills: u8,
+        proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        ctx.accounts.place_order(
+            outcome,
+            side,
+            price,
+            quantity,
+            max_fills,
+            &proof,
+            ctx.remaining_accounts,
+            &ctx.bumps,
+        )
+    }
+
+    pub fn cancel_order(ctx: Context<CancelOrder>, outcome: bool, order_id: u64) -> Result<()> {
+        ctx.accounts.cancel_order(outcome, order_id)
+    }
+
+    /// Withdraws free order book balances for one outcome to the wallet.
+    pub fn settle_funds<'info>(
+        ctx: Context<'_, '_, '_, 'info, SettleFunds<'info>>,
+        outcome: bool,
+    ) -> Result<()> {
+        ctx.accounts.settle_funds(outcome, ctx.remaining_accounts, &ctx.bumps)
+    }
+
+    /// Adds collateral and outcome tokens to the caller's free order book
+    /// balances, where signed orders settle.
+    pub fn deposit_funds<'info>(
+        ctx: Context<'_, '_, '_, 'info, DepositFunds<'info>>,
+        outcome: bool,
+        collateral_amount: u64,
+        token_amount: u64,
+    ) -> Result<()> {
+        ctx.accounts
+            .deposit_funds(outcome, collateral_amount, token_amount, ctx.remaining_accounts, &ctx.bumps)
+    }
+
+    /// Settles a pair of off-chain signed orders. The transaction must carry
+    /// Ed25519 program instructions verifying both makers' signatures over
+    /// `SignedOrder::message`.
+    pub fn match_signed_orders(
+        ctx: Context<MatchSignedOrders>,
+        bid: SignedOrder,
+        ask: SignedOrder,
+        price: u64,
+        quantity: u64,
+    ) -> Result<()> {
+        ctx.accounts.match_signed_orders(bid, ask, price, quantity, &ctx.bumps)
+    }
+
+    pub fn cancel_signed_order(ctx: Context<CancelSignedOrder>, nonce: u64) -> Result<()> {
+        ctx.accounts.cancel_signed_order(nonce, &ctx.bumps)
+    }
+
+    pub fn update_access_policy(
+        ctx: Context<UpdateAccessPolicy>,
+        access_policy: AccessPolicy,
+    ) -> Result<()> {
+        let market = &mut ctx.accounts.market;
+        require!(!market.resolved, ErrorCode::MarketResolved);
+
+        market.access_policy = access_policy;
+
+        emit!(AccessPolicyUpdated {
+            market: market.key(),
+            access_policy,
+        });
+
+        Ok(())
+    }
+
+    pub fn initialize_protocol(ctx: Context<InitializeProtocol>, metadata_base_uri: String) -> Result<()> {
+        require!(metadata_base_uri.len() <= 100, ErrorCode::UriTooLong);
+
+        let config = &mut ctx.accounts.config;
+        config.paused = false;
+        config.protocol_fee_bps = 0;
+        config.metadata_base_uri = metadata_base_uri;
+        config.admin_count = 1;
+        config.bump = ctx.bumps.config;
+
+        let admin_roles = &mut ctx.accounts.admin_roles;
+        admin_roles.member = ctx.accounts.admin.key();
+        admin_roles.roles = Role::Admin.mask();
+        admin_roles.bump = ctx.bumps.admin_roles;
+
+        emit!(RoleGranted {
+            member: admin_roles.member,
+            role: Role::Admin,
+            granted_by: ctx.accounts.admin.key(),
+        });
+
+        Ok(())
+    }
+
+    pub fn grant_role(ctx: Context<GrantRole>, role: Role) -> Result<()> {
+        let member_roles = &mut ctx.accounts.member_roles;
+        require!(!member_roles.has_role(role), ErrorCode::RoleAlreadyGranted);
+
+        member_roles.member = ctx.accounts.member.key();
+        member_roles.roles |= role.mask();
+        member_roles.bump = ctx.bumps.member_roles;
+
+        if role == Role::Admin {
+            let config = &mut ctx.accounts.config;
+            config.admin_count = config.admin_count.checked_add(1).ok_or(ErrorCode::MathOverflow)?;
+        }
+
+        emit!(RoleGranted {
+            member: member_roles.member,
+            role,
+            granted_by: ctx.accounts.admin.key(),
+        });
+
+        Ok(())
+    }
+
+    pub fn revoke_role(ctx: Context<RevokeRole>, role: Role) -> Result<()> {
+        let member_roles = &mut ctx.accounts.member_roles;
+        require!(member_roles.has_role(role), ErrorCode::RoleNotGranted);
+
+        if role == Role::Admin {
+            // Never leave the protocol without an admin.
+            let config = &mut ctx.accounts.config;
+            require!(config.admin_count > 1, ErrorCode::LastAdmin);
+            config.admin_count -= 1;
+        }
+
+        member_roles.roles &= !role.mask();
+
+        emit!(RoleRevoked {
+            member: member_roles.member,
+            role,
+            revoked_by: ctx.accounts.admin.key(),
+        });
+
+        Ok(())
+    }
+
+    /// Changes the base URI for mints created afterwards. Existing metadata
+    /// keeps the URI it was created with.
+    pub fn set_metadata_base_uri(ctx: Context<SetMetadataBaseUri>, metadata_base_uri: String) -> Result<()> {
+        require!(metadata_base_uri.len() <= 100, ErrorCode::UriTooLong);
+
+        let config = &mut ctx.accounts.config;
+        config.metadata_base_uri = metadata_base_uri;
+
+        Ok(())
+    }
+
+    pub fn set_paused(ctx: Context<SetPaused>, paused: bool) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        config.paused = paused;
+
+        emit!(ProtocolPauseChanged {
+            paused,
+            changed_by: ctx.accounts.pauser.key(),
+        });
+
+        Ok(())
+    }
+
+    /// Sends a pool's accrued protocol fees to `destination`.
+    pub fn collect_protocol_fees<'info>(
+        ctx: Context<'_, '_, '_, 'info, CollectProtocolFees<'info>>,
+    ) -> Result<()> {
+        let amount = ctx.accounts.pool.collect_protocol_fees();
+        require!(amount > 0, ErrorCode::NoFeesToClaim);
+
+        let market_key = ctx.accounts.market.key();
+        let seeds = &[
+            b"vault",
+            market_key.as_ref(),
+            &[ctx.bumps.market_vault],
+        ];
+        let signer = &[&seeds[..]];
+
+        collateral::withdraw(
+            &ctx.accounts.token_program.to_account_info(),
+            &ctx.accounts.market_vault,
+            &ctx.accounts.mint,
+            &ctx.accounts.fee_manager.to_account_info(),
+            &ctx.accounts.destination.to_account_info(),
+            ctx.remaining_accounts,
+            amount,
+            signer,
+        )?;
+
+        emit!(ProtocolFeesCollected {
+            market: market_key,
+            amount,
+            collected_by: ctx.accounts.fee_manager.key(),
+        });
+
+        Ok(())
+    }
+
+    pub fn set_protocol_fee(ctx: Context<SetProtocolFee>, protocol_fee_bps: u16) -> Result<()> {
+        require!(protocol_fee_bps <= 1000, ErrorCode::InvalidFeeRate); // Max 10%
+
+        let config = &mut ctx.accounts.config;
+        config.protocol_fee_bps = protocol_fee_bps;
+
+        emit!(ProtocolFeeChanged {
+            protocol_fee_bps,
+            changed_by: ctx.accounts.fee_manager.key(),
+        });
+
+        Ok(())
+    }
+}
+
+/// Settings a market is created with.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct InitializeMarketParams {
+    pub market_id: u64,
+    /// Must equal `Market::question_hash` of the other parameters.
+    pub question_hash: [u8; 32],
+    pub question: String,
+    pub end_time: i64,
+    pub oracle: Pubkey,
+    pub access_policy: AccessPolicy,
+    pub exposure_limits: ExposureLimits,
+    pub schedule: MarketSchedule,
+    pub max_price_impact_bps: u16,
+}
+
+#[derive(Accounts)]
+#[instruction(params: InitializeMarketParams)]
+pub struct InitializeMarket<'info> {
+    // Seeded by the question hash, so a second market on the same question,
+    // end time, oracle and collateral finds this account already filled in.
+    #[account(
+        init_if_needed,
+        payer = creator,
+        space = 8 + Market::INIT_SPACE,
+        seeds = [b"market", params.question_hash.as_ref()],
+        bump,
+        constraint = market.question_hash == [0u8; 32] @ ErrorCode::MarketAlreadyExists
+    )]
+    pub market: Account<'info, Market>,
+    
+    #[account(
+        init,
+        payer = creator,
+        token::mint = mint,
+        token::authority = market_vault,
+        token::token_program = token_program,
+        seeds = [b"vault", market.key().as_ref()],
+        bump
+    )]
+    pub market_vault: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(mut)]
+    pub creator: Signer<'info>,
+
+    #[account(
+        seeds = [b"role", creator.key().as_ref()],
+        bump = creator_roles.bump,
+        constraint = creator_roles.has_role(Role::MarketCreator) @ ErrorCode::MissingRole
+    )]
+    pub creator_roles: Account<'info, RoleAssignment>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Box<Account<'info, ProtocolConfig>>,
+    
+    /// Collateral mint. Pass the native mint for a market settled in SOL.
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        init,
+        payer = creator,
+        mint::decimals = mint.decimals,
+        mint::authority = market,
+        mint::token_program = token_program,
+        seeds = [b"yes_mint", market.key().as_ref()],
+        bump
+    )]
+    pub yes_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        init,
+        payer = creator,
+        mint::decimals = mint.decimals,
+        mint::authority = market,
+        mint::token_program = token_program,
+        seeds = [b"no_mint", market.key().as_ref()],
+        bump
+    )]
+    pub no_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// CHECK: Created by the token metadata program
+    #[account(
+        mut,
+        seeds = [b"metadata", token_metadata_program.key().as_ref(), yes_mint.key().as_ref()],
+        bump,
+        seeds::program = token_metadata_program.key()
+    )]
+    pub yes_metadata: UncheckedAccount<'info>,
+
+    /// CHECK: Created by the token metadata program
+    #[account(
+        mut,
+        seeds = [b"metadata", token_metadata_program.key().as_ref(), no_mint.key().as_ref()],
+        bump,
+        seeds::program = token_metadata_program.key()
+    )]
+    pub no_metadata: UncheckedAccount<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub token_metadata_program: Program<'info, Metadata>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+#[instruction(amount: u64, prediction: bool)]
+pub struct PlaceBet<'info> {
+    #[account(mut)]
+    pub market: Account<'info, Market>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProtocolConfig>,
+    
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = 8 + UserPosition::INIT_SPACE,
+        seeds = [b"position", user.key().as_ref(), market.key().as_ref()],
+        bump
+    )]
+    pub user_position: Account<'info, UserPosition>,
+    
+    #[account(
+        mut,
+        seeds = [b"vault", market.key().as_ref()],
+        bump
+    )]
+    pub market_vault: InterfaceAccount<'info, TokenAccount>,
+    
+    /// Source of SPL collateral. Not needed for native SOL markets, which
+    /// take lamports straight from `user`.
+    #[account(mut)]
+    pub user_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(address = market.collateral_mint)]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        address = market.outcome_mint(prediction) @ ErrorCode::InvalidOutcomeMint
+    )]
+    pub outcome_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = outcome_mint,
+        associated_token::authority = user,
+        associated_token::token_program = token_program
+    )]
+    pub user_outcome_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Holding checked against a token-gated market's access policy.
+    pub gate_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    
+    /// The market's chart history, updated when passed.
+    #[account(
+        mut,
+        seeds = [b"price_history", market.key().as_ref()],
+        bump = price_history.load()?.bump
+    )]
+    pub price_history: Option<AccountLoader<'info, PriceHistory>>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ResolveMarket<'info> {
+    #[account(mut)]
+    pub market: Account<'info, Market>,
+    
+    pub oracle: Signer<'info>,
+
+    #[account(
+        seeds = [b"role", oracle.key().as_ref()],
+        bump = oracle_roles.bump,
+        constraint = oracle_roles.has_role(Role::Resolver) @ ErrorCode::MissingRole
+    )]
+    pub oracle_roles: Account<'info, RoleAssignment>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimWinnings<'info> {
+    #[account(mut)]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        mut,
+        constraint = market.outcome.map(|outcome| market.outcome_mint(outcome)) == Some(winning_mint.key())
+            @ ErrorCode::InvalidOutcomeMint
+    )]
+    pub winning_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        token::mint = winning_mint,
+        token::authority = user
+    )]
+    pub user_outcome_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    
+    #[account(
+        mut,
+        seeds = [b"vault", market.key().as_ref()],
+        bump
+    )]
+    pub market_vault: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(mut)]
+    pub user_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(address = market.collateral_mint)]
+    pub mint: InterfaceAccount<'info, Mint>,
+    
+    #[account(mut)]
+    pub user: Signer<'info>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimRefund<'info> {
+    #[account(mut)]
+    pub market: Account<'info, Market>,
+
+    #[account(mut, address = market.yes_mint @ ErrorCode::InvalidOutcomeMint)]
+    pub yes_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(mut, address = market.no_mint @ ErrorCode::InvalidOutcomeMint)]
+    pub no_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        token::mint = yes_mint,
+        token::authority = user
+    )]
+    pub user_yes_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        token::mint = no_mint,
+        token::authority = user
+    )]
+    pub user_no_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", market.key().as_ref()],
+        bump
+    )]
+    pub market_vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub user_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(address = market.collateral_mint)]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct MintCompleteSet<'info> {
+    #[account(mut)]
+    pub market: Account<'info, Market>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProtocolConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", market.key().as_ref()],
+        bump
+    )]
+    pub market_vault: InterfaceAccount<'info, TokenAccount>,
+
+    /// Source of SPL collateral. Not needed for native SOL markets.
+    #[account(mut)]
+    pub user_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(address = market.collateral_mint)]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(mut, address = market.yes_mint @ ErrorCode::InvalidOutcomeMint)]
+    pub yes_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(mut, address = market.no_mint @ ErrorCode::InvalidOutcomeMint)]
+    pub no_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = yes_mint,
+        associated_token::authority = user,
+        associated_token::token_program = token_program
+    )]
+    pub user_yes_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = no_mint,
+        associated_token::authority = user,
+        associated_token::token_program = token_program
+    )]
+    pub user_no_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Holding checked against a token-gated market's access policy.
+    pub gate_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct MergeCompleteSet<'info> {
+    #[account(mut)]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", market.key().as_ref()],
+        bump
+    )]
+    pub market_vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub user_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(address = market.collateral_mint)]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(mut, address = market.yes_mint @ ErrorCode::InvalidOutcomeMint)]
+    pub yes_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(mut, address = market.no_mint @ ErrorCode::InvalidOutcomeMint)]
+    pub no_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        token::mint = yes_mint,
+        token::authority = user
+    )]
+    pub user_yes_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        token::mint = no_mint,
+        token::authority = user
+    )]
+    pub user_no_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct TransferPosition<'info> {
+    pub market: Account<'info, Market>,
+
+    #[account(
+        mut,
+        seeds = [b"position", user.key().as_ref(), market.key().as_ref()],
+        bump
+    )]
+    pub user_position: Account<'info, UserPosition>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = 8 + UserPosition::INIT_SPACE,
+        seeds = [b"position", recipient.key().as_ref(), market.key().as_ref()],
+        bump
+    )]
+    pub recipient_position: Account<'info, UserPosition>,
+
+    #[account(address = market.yes_mint @ ErrorCode::InvalidOutcomeMint)]
+    pub yes_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(address = market.no_mint @ ErrorCode::InvalidOutcomeMint)]
+    pub no_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        token::mint = yes_mint,
+        token::authority = user
+    )]
+    pub user_yes_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        token::mint = no_mint,
+        token::authority = user
+    )]
+    pub user_no_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = yes_mint,
+        associated_token::authority = recipient,
+        associated_token::token_program = token_program
+    )]
+    pub recipient_yes_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = no_mint,
+        associated_token::authority = recipient,
+        associated_token::token_program = token_program
+    )]
+    pub recipient_no_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// CHECK: Only used as the owner of the receiving position and token accounts
+    pub recipient: UncheckedAccount<'info>,
+
+    /// Recipient's holding checked against a token-gated market's access policy.
+    pub recipient_gate_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct AuditMarket<'info> {
+    pub market: Account<'info, Market>,
+
+    #[account(
+        seeds = [b"vault", market.key().as_ref()],
+        bump
+    )]
+    pub market_vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(address = market.yes_mint @ ErrorCode::InvalidOutcomeMint)]
+    pub yes_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(address = market.no_mint @ ErrorCode::InvalidOutcomeMint)]
+    pub no_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// CHECK: the market's AMM pool PDA, read only if it has been created
+    #[account(seeds = [b"pool", market.key().as_ref()], bump)]
+    pub pool: UncheckedAccount<'info>,
+
+    /// CHECK: the market's LMSR pool PDA, read only if it has been created
+    #[account(seeds = [b"lmsr", market.key().as_ref()], bump)]
+    pub lmsr_pool: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct InitializePool<'info> {
+    #[account(mut, has_one = creator @ ErrorCode::UnauthorizedUser)]
+    pub market: Box<Account<'info, Market>>,
+
+    #[account(
+        init,
+        payer = creator,
+        space = LiquidityPool::LEN,
+        seeds = [b"pool", market.key().as_ref()],
+        bump
+    )]
+    pub pool: Box<Account<'info, LiquidityPool>>,
+
+    #[account(
+        seeds = [b"vault", market.key().as_ref()],
+        bump
+    )]
+    pub market_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(address = market.yes_mint @ ErrorCode::InvalidOutcomeMint)]
+    pub yes_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(address = market.no_mint @ ErrorCode::InvalidOutcomeMint)]
+    pub no_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        init,
+        payer = creator,
+        token::mint = yes_mint,
+        token::authority = pool,
+        token::token_program = token_program,
+        seeds = [b"pool_yes", pool.key().as_ref()],
+        bump
+    )]
+    pub pool_yes_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        init,
+        payer = creator,
+        token::mint = no_mint,
+        token::authority = pool,
+        token::token_program = token_program,
+        seeds = [b"pool_no", pool.key().as_ref()],
+        bump
+    )]
+    pub pool_no_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// LP token for the pool, sharing the outcome tokens' decimals.
+    #[account(
+        init,
+        payer = creator,
+        mint::decimals = yes_mint.decimals,
+        mint::authority = pool,
+        mint::token_program = token_program,
+        seeds = [b"lp_mint", pool.key().as_ref()],
+        bump
+    )]
+    pub lp_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// Holds staked LP tokens, which are the ones that earn fees.
+    #[account(
+        init,
+        payer = creator,
+        token::mint = lp_mint,
+        token::authority = pool,
+        token::token_program = token_program,
+        seeds = [b"lp_vault", pool.key().as_ref()],
+        bump
+    )]
+    pub lp_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// CHECK: Created by the token metadata program
+    #[account(
+        mut,
+        seeds = [b"metadata", token_metadata_program.key().as_ref(), lp_mint.key().as_ref()],
+        bump,
+        seeds::program = token_metadata_program.key()
+    )]
+    pub lp_metadata: UncheckedAccount<'info>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Box<Account<'info, ProtocolConfig>>,
+
+    #[account(mut)]
+    pub creator: Signer<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub token_metadata_program: Program<'info, Metadata>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+pub struct BuyShares<'info> {
+    #[account(mut)]
+    pub market: Box<Account<'info, Market>>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Box<Account<'info, ProtocolConfig>>,
+
+    #[account(
+        mut,
+        seeds = [b"pool", market.key().as_ref()],
+        bump = pool.bump,
+        has_one = market
+    )]
+    pub pool: Box<Account<'info, LiquidityPool>>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = 8 + UserPosition::INIT_SPACE,
+        seeds = [b"position", user.key().as_ref(), market.key().as_ref()],
+        bump
+    )]
+    pub user_position: Box<Account<'info, UserPosition>>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", market.key().as_ref()],
+        bump
+    )]
+    pub market_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Source of SPL collateral. Not needed for native SOL markets.
+    #[account(mut)]
+    pub user_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(address = market.collateral_mint)]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(mut, address = market.yes_mint @ ErrorCode::InvalidOutcomeMint)]
+    pub yes_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(mut, address = market.no_mint @ ErrorCode::InvalidOutcomeMint)]
+    pub no_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(mut, address = pool.yes_token_vault)]
+    pub pool_yes_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut, address = pool.no_token_vault)]
+    pub pool_no_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = yes_mint,
+        associated_token::authority = user,
+        associated_token::token_program = token_program
+    )]
+    pub user_yes_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = no_mint,
+        associated_token::authority = user,
+        associated_token::token_program = token_program
+    )]
+    pub user_no_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Holding checked against a token-gated market's access policy.
+    pub gate_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The market's chart history, updated when passed.
+    #[account(
+        mut,
+        seeds = [b"price_history", market.key().as_ref()],
+        bump = price_history.load()?.bump
+    )]
+    pub price_history: Option<AccountLoader<'info, PriceHistory>>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SellShares<'info> {
+    #[account(mut)]
+    pub market: Box<Account<'info, Market>>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Box<Account<'info, ProtocolConfig>>,
+
+    #[account(
+        mut,
+        seeds = [b"pool", market.key().as_ref()],
+        bump = pool.bump,
+        has_one = market
+    )]
+    pub pool: Box<Account<'info, LiquidityPool>>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = 8 + UserPosition::INIT_SPACE,
+        seeds = [b"position", user.key().as_ref(), market.key().as_ref()],
+        bump
+    )]
+    pub user_position: Box<Account<'info, UserPosition>>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", market.key().as_ref()],
+        bump
+    )]
+    pub market_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub user_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(address = market.collateral_mint)]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(mut, address = market.yes_mint @ ErrorCode::InvalidOutcomeMint)]
+    pub yes_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(mut, address = market.no_mint @ ErrorCode::InvalidOutcomeMint)]
+    pub no_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(mut, address = pool.yes_token_vault)]
+    pub pool_yes_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut, address = pool.no_token_vault)]
+    pub pool_no_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        token::mint = yes_mint,
+        token::authority = user
+    )]
+    pub user_yes_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        token::mint = no_mint,
+        token::authority = user
+    )]
+    pub user_no_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Holding checked against a token-gated market's access policy.
+    pub gate_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The market's chart history, updated when passed.
+    #[account(
+        mut,
+        seeds = [b"price_history", market.key().as_ref()],
+        bump = price_history.load()?.bump
+    )]
+    pub price_history: Option<AccountLoader<'info, PriceHistory>>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetFeeCurve<'info> {
+    #[account(mut)]
+    pub pool: Box<Account<'info, LiquidityPool>>,
+
+    #[account(
+        seeds = [b"role", fee_manager.key().as_ref()],
+        bump = fee_manager_roles.bump,
+        constraint = fee_manager_roles.has_role(Role::FeeManager) @ ErrorCode::MissingRole
+    )]
+    pub fee_manager_roles: Account<'info, RoleAssignment>,
+
+    pub fee_manager: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeLmsr<'info> {
+    #[account(mut, has_one = creator @ ErrorCode::UnauthorizedUser)]
+    pub market: Box<Account<'info, Market>>,
+
+    #[account(
+        init,
+        payer = creator,
+        space = 8 + LmsrPool::INIT_SPACE,
+        seeds = [b"lmsr", market.key().as_ref()],
+        bump
+    )]
+    pub lmsr_pool: Box<Account<'info, LmsrPool>>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", market.key().as_ref()],
+        bump
+    )]
+    pub market_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Source of the SPL subsidy. Not needed for native SOL markets.
+    #[account(mut)]
+    pub creator_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(address = market.collateral_mint)]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(mut)]
+    pub creator: Signer<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(amount: u64, buy_yes: bool)]
+pub struct BuyLmsrShares<'info> {
+    #[account(mut)]
+    pub market: Box<Account<'info, Market>>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Box<Account<'info, ProtocolConfig>>,
+
+    #[account(
+        mut,
+        seeds = [b"lmsr", market.key().as_ref()],
+        bump = lmsr_pool.bump,
+        has_one = market
+    )]
+    pub lmsr_pool: Box<Account<'info, LmsrPool>>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = 8 + UserPosition::INIT_SPACE,
+        seeds = [b"position", user.key().as_ref(), market.key().as_ref()],
+        bump
+    )]
+    pub user_position: Box<Account<'info, UserPosition>>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", market.key().as_ref()],
+        bump
+    )]
+    pub market_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Source of SPL collateral. Not needed for native SOL markets.
+    #[account(mut)]
+    pub user_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(address = market.collateral_mint)]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        address = market.outcome_mint(buy_yes) @ ErrorCode::InvalidOutcomeMint
+    )]
+    pub outcome_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = outcome_mint,
+        associated_token::authority = user,
+        associated_token::token_program = token_program
+    )]
+    pub user_outcome_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Holding checked against a token-gated market's access policy.
+    pub gate_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The market's chart history, updated when passed.
+    #[account(
+        mut,
+        seeds = [b"price_history", market.key().as_ref()],
+        bump = price_history.load()?.bump
+    )]
+    pub price_history: Option<AccountLoader<'info, PriceHistory>>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(shares: u64, sell_yes: bool)]
+pub struct SellLmsrShares<'info> {
+    #[account(mut)]
+    pub market: Box<Account<'info, Market>>,
+
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Box<Account<'info, ProtocolConfig>>,
+
+    #[account(
+        mut,
+        seeds = [b"lmsr", market.key().as_ref()],
+        bump = lmsr_pool.bump,
+        has_one = market
+    )]
+    pub lmsr_pool: Box<Account<'info, LmsrPool>>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = 8 + UserPosition::INIT_SPACE,
+        seeds = [b"position", user.key().as_ref(), market.key().as_ref()],
+        bump
+    )]
+    pub user_position: Box<Account<'info, UserPosition>>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", market.key().as_ref()],
+        bump
+    )]
+    pub market_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub user_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(address = market.collateral_mint)]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        address = market.outcome_mint(sell_yes) @ ErrorCode::InvalidOutcomeMint
+    )]
+    pub outcome_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        token::mint = outcome_mint,
+        token::authority = user
+    )]
+    pub user_outcome_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(address = market.outcome_mint(!sell_yes) @ ErrorCode::InvalidOutcomeMint)]
+    pub other_outcome_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// The user's holding of the other outcome, so the stake released is
+    /// the average cost over everything they hold.
+    #[account(
+        token::mint = other_outcome_mint,
+        token::authority = user
+    )]
+    pub user_other_outcome_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// Holding checked against a token-gated market's access policy.
+    pub gate_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The market's chart history, updated when passed.
+    #[account(
+        mut,
+        seeds = [b"price_history", market.key().as_ref()],
+        bump = price_history.load()?.bump
+    )]
+    pub price_history: Option<AccountLoader<'info, PriceHistory>>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateAccessPolicy<'info> {
+    #[account(mut, has_one = creator @ ErrorCode::UnauthorizedUser)]
+    pub market: Account<'info, Market>,
+
+    pub creator: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeProtocol<'info> {
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + ProtocolConfig::INIT_SPACE,
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + RoleAssignment::INIT_SPACE,
+        seeds = [b"role", admin.key().as_ref()],
+        bump
+    )]
+    pub admin_roles: Account<'info, RoleAssignment>,
+
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()))]
+    pub program: Program<'info, crate::program::Zentro>,
+
+    /// Only the program's upgrade authority may set up the protocol, so the
+    /// first admin can't be claimed by whoever calls first after deployment.
+    #[account(constraint = program_data.upgrade_authority_address == Some(admin.key()) @ ErrorCode::UnauthorizedUser)]
+    pub program_data: Account<'info, ProgramData>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct GrantRole<'info> {
+    #[account(mut, seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProtocolConfig>,
+
+    #[account(
+        init_if_needed,
+        payer = admin,
+        space = 8 + RoleAssignment::INIT_SPACE,
+        seeds = [b"role", member.key().as_ref()],
+        bump
+    )]
+    pub member_roles: Account<'info, RoleAssignment>,
+
+    /// CHECK: Only used as the seed and recipient of the role assignment
+    pub member: UncheckedAccount<'info>,
+
+    #[account(
+        seeds = [b"role", admin.key().as_ref()],
+        bump = admin_roles.bump,
+        constraint = admin_roles.has_role(Role::Admin) @ ErrorCode::MissingRole
+    )]
+    pub admin_roles: Account<'info, RoleAssignment>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RevokeRole<'info> {
+    #[account(mut, seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProtocolConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"role", member_roles.member.as_ref()],
+        bump = member_roles.bump
+    )]
+    pub member_roles: Account<'info, RoleAssignment>,
+
+    #[account(
+        seeds = [b"role", admin.key().as_ref()],
+        bump = admin_roles.bump,
+        constraint = admin_roles.has_role(Role::Admin) @ ErrorCode::MissingRole
+    )]
+    pub admin_roles: Account<'info, RoleAssignment>,
+
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetMetadataBaseUri<'info> {
+    #[account(mut, seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProtocolConfig>,
+
+    #[account(
+        seeds = [b"role", admin.key().as_ref()],
+        bump = admin_roles.bump,
+        constraint = admin_roles.has_role(Role::Admin) @ ErrorCode::MissingRole
+    )]
+    pub admin_roles: Account<'info, RoleAssignment>,
+
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetPaused<'info> {
+    #[account(mut, seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProtocolConfig>,
+
+    #[account(
+        seeds = [b"role", pauser.key().as_ref()],
+        bump = pauser_roles.bump,
+        constraint = pauser_roles.has_role(Role::Pauser) @ ErrorCode::MissingRole
+    )]
+    pub pauser_roles: Account<'info, RoleAssignment>,
+
+    pub pauser: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetProtocolFee<'info> {
+    #[account(mut, seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ProtocolConfig>,
+
+    #[account(
+        seeds = [b"role", fee_manager.key().as_ref()],
+        bump = fee_manager_roles.bump,
+        constraint = fee_manager_roles.has_role(Role::FeeManager) @ ErrorCode::MissingRole
+    )]
+    pub fee_manager_roles: Account<'info, RoleAssignment>,
+
+    pub fee_manager: Signer<'info>,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Market {
+    pub id: u64,
+    #[max_len(200)]
+    pub question: String,
+    pub question_hash: [u8; 32],
+    pub end_time: i64,
+    pub oracle: Pubkey,
+    pub collateral_mint: Pubkey,
+    pub yes_mint: Pubkey,
+    pub no_mint: Pubkey,
+    pub total_yes_tokens: u64,
+    pub total_no_tokens: u64,
+    /// Collateral backing the outstanding YES and NO tokens.
+    pub total_collateral: u64,
+    /// Collateral paid out by claims, refunds and merges after settlement.
+    pub total_claimed: u64,
+    /// Collateral in the vault credited to open-orders balances, free or
+    /// locked behind resting orders.
+    pub book_collateral: u64,
+    pub resolved: bool,
+    pub outcome: Option<bool>,
+    pub creator: Pubkey,
+    pub access_policy: AccessPolicy,
+    pub exposure_limits: ExposureLimits,
+    pub schedule: MarketSchedule,
+    /// Most a single bet or pool trade may move the YES price, in basis
+    /// points. Zero disables the cap. Order-book and signed-order fills are
+    /// not checked, since they trade at prices the maker set.
+    pub max_price_impact_bps: u16,
+    pub voided: bool,
+    pub pricing_engine: PricingEngine,
+    /// Set once the market's price history account exists. Trades must then
+    /// pass it, so no trade goes unrecorded.
+    pub has_price_history: bool,
+    pub bump: u8,
+}
+
+impl Market {
+    /// Fails when the market keeps a price history and the trade left it out.
+    pub fn check_price_history(&self, supplied: bool) -> Result<()> {
+        require!(supplied || !self.has_price_history, ErrorCode::MissingPriceHistory);
+        Ok(())
+    }
+
+    /// Seeds for signing as the market PDA, which is the outcome mint authority.
+    pub fn signer_seeds(&self) -> [&[u8]; 3] {
+        [
+            b"market",
+            self.question_hash.as_ref(),
+            std::slice::from_ref(&self.bump),
+        ]
+    }
+
+    /// Whether claims or refunds can have started, after which the market
+    /// totals are frozen.
+    pub fn is_settled(&self, clock: &Clock) -> bool {
+        self.resolved || self.schedule.resolution_deadline.has_passed(clock)
+    }
+
+    /// Collateral owed for tokens redeemed on a settled market: winning
+    /// tokens at the claim ratio, or every token at the refund ratio if the
+    /// market was never resolved.
+    pub fn settlement_value(&self, yes_tokens: u64, no_tokens: u64) -> Result<u64> {
+        let (tokens, total_tokens) = match self.outcome {
+            Some(true) => (yes_tokens as u128, self.total_yes_tokens as u128),
+            Some(false) => (no_tokens as u128, self.total_no_tokens as u128),
+            None => (
+                yes_tokens as u128 + no_tokens as u128,
+                self.total_yes_tokens as u128 + self.total_no_tokens as u128,
+            ),
+        };
+        if tokens == 0 {
+            return Ok(0);
+        }
+
+        let value = mul_div(tokens, self.redemption_pot() as u128, total_tokens, Rounding::Down)?;
+        u64::try_from(value).map_err(|_| error!(ErrorCode::MathOverflow))
+    }
+
+    /// Collateral shared out among redeemed tokens. A resolved LMSR market
+    /// pays one unit per winning share; the maker's unspent subsidy is left
+    /// to the creator as `lmsr_surplus`. Refunds share everything.
+    pub fn redemption_pot(&self) -> u64 {
+        match self.outcome {
+            Some(outcome) if self.pricing_engine == PricingEngine::Lmsr => {
+                let total_winning = if outcome { self.total_yes_tokens } else { self.total_no_tokens };
+                total_winning.min(self.total_collateral)
+            }
+            _ => self.total_collateral,
+        }
+    }
+
+    /// Collateral left over after a resolved LMSR market pays its winners.
+    pub fn lmsr_surplus(&self) -> u64 {
+        self.total_collateral - self.redemption_pot()
+    }
+
+    /// Checks the exposure limits for a user with `user_staked` adding
+    /// `received` to the market. Returns the user's new stake.
+    pub fn check_exposure(&self, user_staked: u64, received: u64) -> Result<u64> {
+        let user_staked = user_staked.checked_add(received).ok_or(ErrorCode::MathOverflow)?;
+        let open_interest = self
+            .total_yes_tokens
+            .checked_add(self.total_no_tokens)
+            .and_then(|total| total.checked_add(received))
+            .ok_or(ErrorCode::MathOverflow)?;
+        self.exposure_limits.check(user_staked, open_interest)?;
+        Ok(user_staked)
+    }
+
+    /// Parimutuel YES odds in basis points: the YES share of all outcome
+    /// tokens, or even odds before any bets.
+    pub fn yes_odds(&self) -> Result<u64> {
+        let total_tokens = self
+            .total_yes_tokens
+            .checked_add(self.total_no_tokens)
+            .ok_or(ErrorCode::MathOverflow)?;
+        if total_tokens == 0 {
+            return Ok(BPS / 2);
+        }
+        mul_div_u64(self.total_yes_tokens, BPS, total_tokens, Rounding::Down)
+    }
+
+    /// Adds a bet of `received` to the market totals and returns the YES
+    /// odds after it. The first bet sets the odds rather than moving them,
+    /// so only later bets are held to the price impact cap.
+    pub fn add_bet(&mut self, prediction: bool, received: u64) -> Result<u64> {
+        let yes_odds_before = self.yes_odds()?;
+        let had_bets = self.total_yes_tokens > 0 || self.total_no_tokens > 0;
+        if prediction {
+            self.total_yes_tokens = self.total_yes_tokens.checked_add(received).ok_or(ErrorCode::MathOverflow)?;
+        } else {
+            self.total_no_tokens = self.total_no_tokens.checked_add(received).ok_or(ErrorCode::MathOverflow)?;
+        }
+        self.total_collateral = self.total_collateral.checked_add(received).ok_or(ErrorCode::MathOverflow)?;
+
+        let yes_odds = self.yes_odds()?;
+        if had_bets {
+            self.check_price_impact(yes_odds_before, yes_odds)?;
+        }
+        Ok(yes_odds)
+    }
+
+    /// Rejects a trade that moved the YES price from `yes_price_before` to
+    /// `yes_price_after` by more than `max_price_impact_bps`.
+    pub fn check_price_impact(&self, yes_price_before: u64, yes_price_after: u64) -> Result<()> {
+        if self.max_price_impact_bps > 0 {
+            require!(
+                yes_price_after.abs_diff(yes_price_before) <= self.max_price_impact_bps as u64,
+                ErrorCode::PriceImpactExceeded
+            );
+        }
+        Ok(())
+    }
+
+    /// Buys from the constant-product pool with `received` collateral, which
+    /// is minted into complete sets for the pool. `buy_shares` runs this on
+    /// the accounts and `quote_buy` on copies.
+    pub fn buy_from_pool(
+        &mut self,
+        pool: &mut LiquidityPool,
+        received: u64,
+        buy_yes: bool,
+        now: i64,
+        protocol_fee_bps: u16,
+    ) -> Result<PoolTrade> {
+        pool.accumulate_price(now)?;
+        let fee_rate = pool.current_fee_rate(now, self.end_time)?;
+        let yes_price_before = pool.get_price(true)?;
+        let (amount_out, fee) = pool.execute_buy(received, buy_yes, fee_rate, protocol_fee_bps)?;
+        pool.record_price_move(yes_price_before, now)?;
+        let yes_price_after = pool.get_price(true)?;
+        self.check_price_impact(yes_price_before, yes_price_after)?;
+
+        let net_amount = received - fee;
+        self.total_yes_tokens = self.total_yes_tokens.checked_add(net_amount).ok_or(ErrorCode::MathOverflow)?;
+        self.total_no_tokens = self.total_no_tokens.checked_add(net_amount).ok_or(ErrorCode::MathOverflow)?;
+        self.total_collateral = self.total_collateral.checked_add(net_amount).ok_or(ErrorCode::MathOverflow)?;
+
+        Ok(PoolTrade { amount_out, fee, fee_rate, yes_price_before, yes_price_after })
+    }
+
+    /// Sells `shares` to the constant-product pool, which merges complete
+    /// sets back into collateral. Shared by `sell_shares` and `quote_sell`.
+    pub fn sell_to_pool(
+        &mut self,
+        pool: &mut LiquidityPool,
+        shares: u64,
+        sell_yes: bool,
+        now: i64,
+        protocol_fee_bps: u16,
+    ) -> Result<PoolTrade> {
+        pool.accumulate_price(now)?;
+        let fee_rate = pool.current_fee_rate(now, self.end_time)?;
+        let yes_price_before = pool.get_price(true)?;
+        let (amount_out, fee) = pool.execute_sell(shares, sell_yes, fee_rate, protocol_fee_bps)?;
+        pool.record_price_move(yes_price_before, now)?;
+        let yes_price_after = pool.get_price(true)?;
+        self.check_price_impact(yes_price_before, yes_price_after)?;
+
+        let merged = amount_out + fee;
+        self.total_yes_tokens = self.total_yes_tokens.checked_sub(merged).ok_or(ErrorCode::MathOverflow)?;
+        self.total_no_tokens = self.total_no_tokens.checked_sub(merged).ok_or(ErrorCode::MathOverflow)?;
+        self.total_collateral = self.total_collateral.checked_sub(merged).ok_or(ErrorCode::MathOverflow)?;
+
+        Ok(PoolTrade { amount_out, fee, fee_rate, yes_price_before, yes_price_after })
+    }
+
+    /// Buys from the LMSR maker with `received` collateral, which backs the
+    /// newly minted shares. Shared by `buy_lmsr_shares` and `quote_lmsr_buy`.
+    pub fn buy_from_lmsr(&mut self, lmsr_pool: &mut LmsrPool, received: u64, buy_yes: bool) -> Result<PoolTrade> {
+        let yes_price_before = lmsr_pool.get_price(true)?;
+        let (amount_out, fee) = lmsr_pool.execute_buy(received, buy_yes)?;
+        let yes_price_after = lmsr_pool.get_price(true)?;
+        self.check_price_impact(yes_price_before, yes_price_after)?;
+
+        if buy_yes {
+            self.total_yes_tokens = self.total_yes_tokens.checked_add(amount_out).ok_or(ErrorCode::MathOverflow)?;
+        } else {
+            self.total_no_tokens = self.total_no_tokens.checked_add(amount_out).ok_or(ErrorCode::MathOverflow)?;
+        }
+        self.total_collateral = self.total_collateral.checked_add(received - fee).ok_or(ErrorCode::MathOverflow)?;
+
+        Ok(PoolTrade { amount_out, fee, fee_rate: lmsr_pool.fee_rate, yes_price_before, yes_price_after })
+    }
+
+    /// Sells `shares` to the LMSR maker, burning them. Shared by
+    /// `sell_lmsr_shares` and `quote_lmsr_sell`.
+    pub fn sell_to_lmsr(&mut self, lmsr_pool: &mut LmsrPool, shares: u64, sell_yes: bool) -> Result<PoolTrade> {
+        let yes_price_before = lmsr_pool.get_price(true)?;
+        let (amount_out, fee) = lmsr_pool.execute_sell(shares, sell_yes)?;
+        let yes_price_after = lmsr_pool.get_price(true)?;
+        self.check_price_impact(yes_price_before, yes_price_after)?;
+
+        if sell_yes {
+            self.total_yes_tokens = self.total_yes_tokens.checked_sub(shares).ok_or(ErrorCode::MathOverflow)?;
+        } else {
+            self.total_no_tokens = self.total_no_tokens.checked_sub(shares).ok_or(ErrorCode::MathOverflow)?;
+        }
+        self.total_collateral = self.total_collateral.checked_sub(amount_out + fee).ok_or(ErrorCode::MathOverflow)?;
+
+        Ok(PoolTrade { amount_out, fee, fee_rate: lmsr_pool.fee_rate, yes_price_before, yes_price_after })
+    }
+
+    pub fn outcome_mint(&self, outcome: bool) -> Pubkey {
+        if outcome {
+            self.yes_mint
+        } else {
+            self.no_mint
+        }
+    }
+
+    /// Canonical form of a question: runs of `QUESTION_WHITESPACE` collapsed
+    /// to a single space, surrounding whitespace trimmed and ASCII
+    /// lowercased. Other characters, including Unicode spaces, are kept so
+    /// clients can reproduce the hash byte for byte.
+    pub fn normalize_question(question: &str) -> String {
+        question
+            .split(Self::QUESTION_WHITESPACE)
+            .filter(|word| !word.is_empty())
+            .collect::<Vec<_>>()
+            .join(" ")
+            .to_ascii_lowercase()
+    }
+
+    /// Space, tab, line feed, form feed and carriage return. The client's
+    /// `normalizeQuestion` splits on the same set.
+    pub const QUESTION_WHITESPACE: [char; 5] = [' ', '\t', '\n', '\x0c', '\r'];
+
+    /// Hash used as the market PDA seed. Clients can derive the market
+    /// address for a question directly instead of scanning program accounts.
+    pub fn question_hash(
+        question: &str,
+        end_time: i64,
+        oracle: &Pubkey,
+        collateral_mint: &Pubkey,
+    ) -> [u8; 32] {
+        hashv(&[
+            Self::normalize_question(question).as_bytes(),
+            &end_time.to_le_bytes(),
+            oracle.as_ref(),
+            collateral_mint.as_ref(),
+        ])
+        .to_bytes()
+    }
+}
+
+/// How a market's shares are priced once trading opens. A market starts
+/// with bets only and can take one market maker.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, InitSpace)]
+pub enum PricingEngine {
+    /// Bets mint shares one-for-one and winners split the pot.
+    Parimutuel,
+    /// Constant-product `LiquidityPool` funded by liquidity providers.
+    ConstantProduct,
+    /// LMSR maker subsidised by the market creator.
+    Lmsr,
+}
+
+/// A point in a market's schedule, as either a unix timestamp or a slot.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, InitSpace)]
+pub enum ScheduleTime {
+    Timestamp(i64),
+    Slot(u64),
+}
+
+impl ScheduleTime {
+    pub fn has_passed(&self, clock: &Clock) -> bool {
+        match self {
+            ScheduleTime::Timestamp(timestamp) => clock.unix_timestamp >= *timestamp,
+            ScheduleTime::Slot(slot) => clock.slot >= *slot,
+        }
+    }
+
+    /// Orders two points of the same kind; timestamps and slots can't be compared.
+    pub fn is_before(&self, other: &ScheduleTime) -> Option<bool> {
+        match (self, other) {
+            (ScheduleTime::Timestamp(a), ScheduleTime::Timestamp(b)) => Some(a < b),
+            (ScheduleTime::Slot(a), ScheduleTime::Slot(b)) => Some(a < b),
+            _ => None,
+        }
+    }
+}
+
+/// Trading is allowed from `open_time` until `close_time`. A market still
+/// unresolved at `resolution_deadline` is void and refunds every stake.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, InitSpace)]
+pub struct MarketSchedule {
+    pub open_time: ScheduleTime,
+    pub close_time: ScheduleTime,
+    pub resolution_deadline: ScheduleTime,
+}
+
+impl MarketSchedule {
+    pub fn validate(&self, end_time: i64) -> Result<()> {
+        require!(
+            self.open_time.is_before(&self.close_time) != Some(false),
+            ErrorCode::InvalidSchedule
+        );
+        require!(
+            self.close_time.is_before(&self.resolution_deadline) != Some(false),
+            ErrorCode::InvalidSchedule
+        );
+        // The deadline has to leave room to resolve after the event ends.
+        require!(
+            ScheduleTime::Timestamp(end_time).is_before(&self.resolution_deadline) != Some(false),
+            ErrorCode::InvalidSchedule
+        );
+        Ok(())
+    }
+
+    pub fn require_trading_open(&self, clock: &Clock) -> Result<()> {
+        require!(self.open_time.has_passed(clock), ErrorCode::MarketNotOpen);
+        require!(!self.close_time.has_passed(clock), ErrorCode::MarketClosed);
+        Ok(())
+    }
+}
+
+/// A trade against one of the market makers, as run by the handlers and
+/// their quotes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PoolTrade {
+    /// Shares bought, or collateral paid for shares sold after the fee.
+    pub amount_out: u64,
+    pub fee: u64,
+    pub fee_rate: u16,
+    /// YES price in basis points before and after the trade.
+    pub yes_price_before: u64,
+    pub yes_price_after: u64,
+}
+
+impl PoolTrade {
+    /// How far the trade moves the price, in basis points.
+    pub fn price_impact(&self) -> u64 {
+        self.yes_price_after.abs_diff(self.yes_price_before)
+    }
+}
+
+/// Result of `audit_market`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AuditReport {
+    pub vault_balance: u64,
+    pub total_collateral: u64,
+    pub total_claimed: u64,
+    /// Collateral held for open-orders balances.
+    pub book_collateral: u64,
+    /// AMM, LMSR and protocol fees, and LMSR surplus, not yet claimed.
+    pub unpaid_fees: u64,
+    /// Collateral still owed to holders of outstanding outcome tokens,
+    /// plus `book_collateral` and `unpaid_fees`.
+    pub outstanding_obligations: u64,
+    pub yes_supply: u64,
+    pub no_supply: u64,
+    /// Vault balance covers `outstanding_obligations`.
+    pub solvent: bool,
+    /// Outcome mint supplies agree with the market totals.
+    pub supply_matches: bool,
+}
+
+impl AuditReport {
+    pub fn new(
+        market: &Market,
+        vault_balance: u64,
+        yes_supply: u64,
+        no_supply: u64,
+        deadline_passed: bool,
+        unpaid_fees: u64,
+    ) -> Result<Self> {
+        let total_tokens = market.total_yes_tokens as u128 + market.total_no_tokens as u128;
+
+        // Claims redeem at ratios fixed by the market totals, so what is
+        // owed is the outstanding supply valued at the same ratio.
+        let (redemptions, supply_matches) = if let Some(outcome) = market.outcome {
+            let (winning_supply, total_winning) = if outcome {
+                (yes_supply, market.total_yes_tokens)
+            } else {
+                (no_supply, market.total_no_tokens)
+            };
+            let owed = if total_winning == 0 {
+                0
+            } else {
+                mul_div(
+                    winning_supply as u128,
+                    market.redemption_pot() as u128,
+                    total_winning as u128,
+                    Rounding::Down,
+                )?
+            };
+            (owed, winning_supply <= total_winning)
+        } else if deadline_passed {
+            let outstanding = yes_supply as u128 + no_supply as u128;
+            let owed = if total_tokens == 0 {
+                0
+            } else {
+                mul_div(outstanding, market.total_collateral as u128, total_tokens, Rounding::Down)?
+            };
+            (owed, outstanding <= total_tokens)
+        } else {
+            (
+                market.total_collateral as u128,
+                yes_supply == market.total_yes_tokens && no_supply == market.total_no_tokens,
+            )
+        };
+        let outstanding_obligations = redemptions + market.book_collateral as u128 + unpaid_fees as u128;
+
+        Ok(AuditReport {
+            vault_balance,
+            total_collateral: market.total_collateral,
+            total_claimed: market.total_claimed,
+            book_collateral: market.book_collateral,
+            unpaid_fees,
+            outstanding_obligations: u64::try_from(outstanding_obligations).map_err(|_| ErrorCode::MathOverflow)?,
+            yes_supply,
+            no_supply,
+            solvent: vault_balance as u128 >= outstanding_obligations,
+            supply_matches,
+        })
+    }
+}
+
+/// Who may trade in a market.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, InitSpace)]
+pub enum AccessPolicy {
+    Open,
+    /// Wallets proven against a Merkle root whose leaves are `hash(wallet)`.
+    Allowlist { merkle_root: [u8; 32] },
+    /// Wallets holding at least `min_amount` of `mint` (1 for an NFT).
+    TokenGated { mint: Pubkey, min_amount: u64 },
+}
+
+impl AccessPolicy {
+    pub fn check(
+        &self,
+        user: &Pubkey,
+        proof: &[[u8; 32]],
+        gate_token_account: Option<&TokenAccount>,
+    ) -> Result<()> {
+        match self {
+            AccessPolicy::Open => Ok(()),
+            AccessPolicy::Allowlist { merkle_root } => {
+                require!(
+                    verify_merkle_proof(proof, merkle_root, hashv(&[user.as_ref()]).to_bytes()),
+                    ErrorCode::NotAllowlisted
+                );
+                Ok(())
+            }
+            AccessPolicy::TokenGated { mint, min_amount } => {
+                let holding = gate_token_account.ok_or(ErrorCode::MissingGateTokenAccount)?;
+                require!(
+                    holding.owner == *user && holding.mint == *mint,
+                    ErrorCode::InvalidGateTokenAccount
+                );
+                require!(holding.amount >= *min_amount, ErrorCode::InsufficientGateTokens);
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Verifies a proof built with sorted pair hashing, so sibling order does
+/// not need to be encoded in the proof.
+pub fn verify_merkle_proof(proof: &[[u8; 32]], root: &[u8; 32], leaf: [u8; 32]) -> bool {
+    let computed = proof.iter().fold(leaf, |node, sibling| {
+        if node <= *sibling {
+            hashv(&[&node, sibling]).to_bytes()
+        } else {
+            hashv(&[sibling, &node]).to_bytes()
+        }
+    });
+    computed == *root
+}
+
+/// Caps on how much stake a market accepts. Zero disables a limit.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq, InitSpace)]
+pub struct ExposureLimits {
+    /// Cumulative stake a single user may place.
+    pub max_user_stake: u64,
+    /// Share of the pool a single user's stake may reach, in basis points.
+    pub max_user_share_bps: u16,
+    /// Pool size below which `max_user_share_bps` is not enforced, so the
+    /// first bettors are not locked out of an empty market.
+    pub share_cap_min_pool: u64,
+    /// Total stake the market accepts across both sides.
+    pub max_open_interest: u64,
+}
+
+impl ExposureLimits {
+    /// Checks a user's cumulative stake and the market's open interest as
+    /// they would be after the bet.
+    pub fn check(&self, user_staked: u64, open_interest: u64) -> Result<()> {
+        if self.max_user_stake > 0 {
+            require!(user_staked <= self.max_user_stake, ErrorCode::UserExposureExceeded);
+        }
+
+        if self.max_user_share_bps > 0 && open_interest >= self.share_cap_min_pool {
+            let max_share = mul_div_u64(open_interest, self.max_user_share_bps as u64, BPS, Rounding::Down)?;
+            require!(user_staked <= max_share, ErrorCode::UserShareExceeded);
+        }
+
+        if self.max_open_interest > 0 {
+            require!(open_interest <= self.max_open_interest, ErrorCode::OpenInterestExceeded);
+        }
+
+        Ok(())
+    }
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct UserPosition {
+    pub user: Pubkey,
+    pub market: Pubkey,
+    /// Cumulative stake placed by the user, checked against `ExposureLimits`.
+    /// Holdings themselves are the user's YES and NO token balances.
+    pub total_staked: u64,
+}
+
+impl UserPosition {
+    /// Takes the average cost of `shares` out of `holdings` tokens off the
+    /// stake. Returns the stake released.
+    pub fn release_stake(&mut self, shares: u64, holdings: u64) -> Result<u64> {
+        let released = if shares >= holdings {
+            self.total_staked
+        } else {
+            mul_div_u64(self.total_staked, shares, holdings, Rounding::Down)?
+        };
+        self.total_staked -= released;
+        Ok(released)
+    }
+}
+
+#[derive(Accounts)]
+pub struct CollectProtocolFees<'info> {
+    pub market: Box<Account<'info, Market>>,
+
+    #[account(
+        mut,
+        seeds = [b"pool", market.key().as_ref()],
+        bump = pool.bump,
+        has_one = market
+    )]
+    pub pool: Box<Account<'info, LiquidityPool>>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", market.key().as_ref()],
+        bump
+    )]
+    pub market_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut, token::mint = mint)]
+    pub destination: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(address = market.collateral_mint)]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        seeds = [b"role", fee_manager.key().as_ref()],
+        bump = fee_manager_roles.bump,
+        constraint = fee_manager_roles.has_role(Role::FeeManager) @ ErrorCode::MissingRole
+    )]
+    pub fee_manager_roles: Account<'info, RoleAssignment>,
+
+    #[account(mut)]
+    pub fee_manager: Signer<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct ProtocolConfig {
+    pub paused: bool,
+    /// Protocol's cut of liquidity pool trading fees, in basis points.
+    pub protocol_fee_bps: u16,
+    pub admin_count: u32,
+    /// Base of the metadata URIs given to market mints.
+    #[max_len(100)]
+    pub metadata_base_uri: String,
+    pub bump: u8,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Role {
+    Admin,
+    MarketCreator,
+    Resolver,
+    Pauser,
+    FeeManager,
+    Operator,
+}
+
+impl Role {
+    pub fn mask(self) -> u8 {
+        1 << self as u8
+    }
+}
+
+/// Roles held by a single wallet, stored as a bitmask of `Role::mask` values.
+#[account]
+#[derive(InitSpace)]
+pub struct RoleAssignment {
+    pub member: Pubkey,
+    pub roles: u8,
+    pub bump: u8,
+}
+
+impl RoleAssignment {
+    pub fn has_role(&self, role: Role) -> bool {
+        self.roles & role.mask() != 0
+    }
+}
+
+#[event]
+pub struct BetPlaced {
+    pub market: Pubkey,
+    pub user: Pubkey,
+    pub amount: u64,
+    pub prediction: bool,
+}
+
+#[event]
+pub struct MarketResolved {
+    pub market: Pubkey,
+    pub outcome: bool,
+}
+
+#[event]
+pub struct WinningsClaimed {
+    pub market: Pubkey,
+    pub user: Pubkey,
+    pub amount: u64,
+    pub tokens_burned: u64,
+}
+
+#[event]
+pub struct CompleteSetMinted {
+    pub market: Pubkey,
+    pub user: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct CompleteSetMerged {
+    pub market: Pubkey,
+    pub user: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct PositionTransferred {
+    pub market: Pubkey,
+    pub from: Pubkey,
+    pub to: Pubkey,
+    pub yes_amount: u64,
+    pub no_amount: u64,
+}
+
+#[event]
+pub struct SolvencyViolation {
+    pub market: Pubkey,
+    pub report: AuditReport,
+}
+
+#[event]
+pub struct SharesTraded {
+    pub market: Pubkey,
+    pub user: Pubkey,
+    pub is_buy: bool,
+    /// `true` for YES shares, `false` for NO.
+    pub outcome: bool,
+    /// Collateral paid in on a buy, or received after fees on a sell.
+    pub collateral_amount: u64,
+    pub shares: u64,
+    pub fee: u64,
+    /// Fee rate charged on this trade, in basis points, including any
+    /// dynamic fee from the pool's fee curve.
+    pub fee_rate: u16,
+    /// Pool YES price after the trade, in basis points.
+    pub yes_price: u64,
+}
+
+#[event]
+pub struct ProtocolFeesCollected {
+    pub market: Pubkey,
+    pub amount: u64,
+    pub collected_by: Pubkey,
+}
+
+#[event]
+pub struct FeeCurveUpdated {
+    pub market: Pubkey,
+    pub fee_curve: Option<FeeCurve>,
+}
+
+#[event]
+pub struct MarketVoided {
+    pub market: Pubkey,
+}
+
+#[event]
+pub struct RefundClaimed {
+    pub market: Pubkey,
+    pub user: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct AccessPolicyUpdated {
+    pub market: Pubkey,
+    pub access_policy: AccessPolicy,
+}
+
+#[event]
+pub struct RoleGranted {
+    pub member: Pubkey,
+    pub role: Role,
+    pub granted_by: Pubkey,
+}
+
+#[event]
+pub struct RoleRevoked {
+    pub member: Pubkey,
+    pub role: Role,
+    pub revoked_by: Pubkey,
+}
+
+#[event]
+pub struct ProtocolPauseChanged {
+    pub paused: bool,
+    pub changed_by: Pubkey,
+}
+
+#[event]
+pub struct ProtocolFeeChanged {
+    pub protocol_fee_bps: u16,
+    pub changed_by: Pubkey,
+}
+
+#[error_code]
+pub enum ErrorCode {
+    #[msg("Market has already been resolved")]
+    MarketResolved,
+    #[msg("Market has expired")]
+    MarketExpired,
+    #[msg("Invalid bet amount")]
+    InvalidAmount,
+    #[msg("Market already resolved")]
+    MarketAlreadyResolved,
+    #[msg("Unauthorized oracle")]
+    UnauthorizedOracle,
+    #[msg("Market not expired yet")]
+    MarketNotExpired,
+    #[msg("Market not resolved")]
+    MarketNotResolved,
+    #[msg("Unauthorized user")]
+    UnauthorizedUser,
+    #[msg("Already claimed")]
+    AlreadyClaimed,
+    #[msg("No winnings to claim")]
+    NoWinnings,
+    #[msg("Question is too long")]
+    QuestionTooLong,
+    #[msg("Question hash does not match the market parameters")]
+    InvalidQuestionHash,
+    #[msg("A market already exists for this question")]
+    MarketAlreadyExists,
+    #[msg("Signer does not hold the required role")]
+    MissingRole,
+    #[msg("Role already granted")]
+    RoleAlreadyGranted,
+    #[msg("Role not granted")]
+    RoleNotGranted,
+    #[msg("Cannot revoke the last admin")]
+    LastAdmin,
+    #[msg("Protocol is paused")]
+    ProtocolPaused,
+    #[msg("Invalid fee rate")]
+    InvalidFeeRate,
+    #[msg("Arithmetic overflow")]
+    MathOverflow,
+    #[msg("Wallet is not on the market allowlist")]
+    NotAllowlisted,
+    #[msg("Token-gated market requires a gate token account")]
+    MissingGateTokenAccount,
+    #[msg("Gate token account has the wrong owner or mint")]
+    InvalidGateTokenAccount,
+    #[msg("Not enough gate tokens held")]
+    InsufficientGateTokens,
+    #[msg("Invalid percentage value")]
+    InvalidPercentage,
+    #[msg("Bet exceeds the per-user stake cap")]
+    UserExposureExceeded,
+    #[msg("Bet exceeds the per-user share of the pool")]
+    UserShareExceeded,
+    #[msg("Bet exceeds the market open interest ceiling")]
+    OpenInterestExceeded,
+    #[msg("Market schedule is out of order")]
+    InvalidSchedule,
+    #[msg("Market is not open for trading yet")]
+    MarketNotOpen,
+    #[msg("Market is closed for trading")]
+    MarketClosed,
+    #[msg("Resolution deadline has passed")]
+    ResolutionDeadlinePassed,
+    #[msg("Resolution deadline has not passed")]
+    ResolutionDeadlineNotReached,
+    #[msg("SPL collateral requires a user token account")]
+    MissingUserTokenAccount,
+    #[msg("Outcome mint does not belong to this market")]
+    InvalidOutcomeMint,
+    #[msg("URI is too long")]
+    UriTooLong,
+    #[msg("Cannot transfer a position to the same wallet")]
+    InvalidRecipient,
+    #[msg("Liquidity pool is inactive")]
+    PoolInactive,
+    #[msg("Insufficient liquidity")]
+    InsufficientLiquidity,
+    #[msg("Trade output is below the minimum amount")]
+    SlippageExceeded,
+    #[msg("Math function argument out of range")]
+    MathDomainError,
+    #[msg("Market already has a market maker")]
+    PricingEngineAlreadySet,
+    #[msg("Deposit does not cover the market maker subsidy")]
+    InsufficientSubsidy,
+    #[msg("Fee curve windows must be positive")]
+    InvalidFeeCurve,
+    #[msg("No fees to claim")]
+    NoFeesToClaim,
+    #[msg("Market already has outcome tokens outstanding")]
+    MarketHasBets,
+    #[msg("Market is not priced by this engine")]
+    WrongPricingEngine,
+    #[msg("Market is settled")]
+    MarketSettled,
+    #[msg("Account is not the pool's LP mint")]
+    InvalidLpMint,
+    #[msg("Order price must be between 1 and 9999 basis points")]
+    InvalidOrderPrice,
+    #[msg("Order size must be a positive multiple of the lot size")]
+    InvalidOrderSize,
+    #[msg("Order book side is full")]
+    OrderBookFull,
+    #[msg("Order not found")]
+    OrderNotFound,
+    #[msg("Order would fill against your own resting order")]
+    SelfTrade,
+    #[msg("Order crosses more resting orders than maker accounts supplied")]
+    TooManyFills,
+    #[msg("Maker account does not match the resting order")]
+    InvalidMakerAccount,
+    #[msg("Open orders balance is too low")]
+    InsufficientBalance,
+    #[msg("Transaction has no Ed25519 signature for the order")]
+    MissingSignature,
+    #[msg("Signed order does not match this settlement")]
+    InvalidSignedOrder,
+    #[msg("Signed order has expired")]
+    OrderExpired,
+    #[msg("Settlement price is outside the orders' limits")]
+    OrdersDoNotCross,
+    #[msg("Signed order was cancelled")]
+    OrderAlreadyCancelled,
+    #[msg("Fill exceeds the order's remaining quantity")]
+    OrderOverfilled,
+    #[msg("Price observations must be in time order")]
+    InvalidObservationWindow,
+    #[msg("Candle interval must be positive")]
+    InvalidInterval,
+    #[msg("Trade moves the price by more than the market's maximum price impact")]
+    PriceImpactExceeded,
+    #[msg("Trade would move the price past the caller's price limit")]
+    PriceLimitExceeded,
+    #[msg("Market records its price history; pass the price history account")]
+    MissingPriceHistory,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_question_hash_matches_client_vectors() {
+        use std::str::FromStr;
+
+        // Shared with the client tests, which must produce the same hashes
+        let vectors: Vec<serde_json::Value> =
+            serde_json::from_str(include_str!("../tests/fixtures/question_hash.json")).unwrap();
+        for vector in vectors {
+            let question = vector["question"].as_str().unwrap();
+            assert_eq!(Market::normalize_question(question), vector["normalized"].as_str().unwrap());
+
+            let hash = Market::question_hash(
+                question,
+                vector["endTime"].as_i64().unwrap(),
+                &Pubkey::from_str(vector["oracle"].as_str().unwrap()).unwrap(),
+                &Pubkey::from_str(vector["collateralMint"].as_str().unwrap()).unwrap(),
+            );
+            let hex: String = hash.iter().map(|byte| format!("{byte:02x}")).collect();
+            assert_eq!(hex, vector["hash"].as_str().unwrap(), "{question:?}");
+        }
+    }
+
+    fn market() -> Market {
+        let zeroed = [0u8; Market::INIT_SPACE];
+        let mut market = Market::deserialize(&mut &zeroed[..]).unwrap();
+        market.total_yes_tokens = 600;
+        market.total_no_tokens = 400;
+        market.total_collateral = 1_000;
+        market
+    }
+
+    #[test]
+    fn test_audit_counts_book_collateral_and_fees() {
+        let mut market = market();
+        market.book_collateral = 250;
+
+        let report = AuditReport::new(&market, 1_300, 600, 400, false, 50).unwrap();
+        assert_eq!(report.outstanding_obligations, 1_300);
+        assert!(report.solvent);
+        assert!(report.supply_matches);
+    }
+
+    #[test]
+    fn test_audit_fails_on_unpaid_fees() {
+        let market = market();
+
+        // The vault only covers the outcome tokens, not the fees still owed
+        let report = AuditReport::new(&market, 1_000, 600, 400, false, 30).unwrap();
+        assert_eq!(report.unpaid_fees, 30);
+        assert_eq!(report.outstanding_obligations, 1_030);
+        assert!(!report.solvent);
+    }
+
+    #[test]
+    fn test_first_bet_exempt_from_impact_cap() {
+        let zeroed = [0u8; Market::INIT_SPACE];
+        let mut market = Market::deserialize(&mut &zeroed[..]).unwrap();
+        market.max_price_impact_bps = 500;
+
+        // The opening bet takes YES odds from 50% to 100%
+        assert_eq!(market.add_bet(true, 1_000).unwrap(), BPS);
+
+        // Later bets are capped: 1,000 NO would move the odds by 5,000 bps
+        assert!(market.clone().add_bet(false, 1_000).is_err());
+        // 100 NO moves them by 909 bps, 50 NO by 476
+        assert!(market.clone().add_bet(false, 100).is_err());
+        assert_eq!(market.add_bet(false, 50).unwrap(), 9_523);
+    }
+
+    #[test]
+    fn test_price_history_required_once_initialized() {
+        let mut market = market();
+        assert!(market.check_price_history(false).is_ok());
+
+        market.has_price_history = true;
+        assert!(market.check_price_history(true).is_ok());
+        assert_eq!(
+            market.check_price_history(false).unwrap_err(),
+            ErrorCode::MissingPriceHistory.into()
+        );
+    }
+
+    fn pool() -> LiquidityPool {
+        let mut pool = LiquidityPool::deserialize(&mut &[0u8; LiquidityPool::LEN - 8][..]).unwrap();
+        pool.yes_reserves = 1_000;
+        pool.no_reserves = 1_000;
+        pool.fee_rate = 100;
+        pool.is_active = true;
+        pool
+    }
+
+    #[test]
+    fn test_pool_quotes_match_trades() {
+        let (mut market, mut pool) = (market(), pool());
+
+        // Quote on copies, then trade on the accounts as the handlers do
+        let mut quoted = market.clone();
+        let trade = quoted.buy_from_pool(&mut pool.clone(), 200, true, 100, 0).unwrap();
+        let buy = BuyQuote::new(&trade, 0, quoted, true).unwrap();
+        market.buy_from_pool(&mut pool, 200, true, 100, 0).unwrap();
+
+        assert_eq!(buy.fee, 2);
+        assert_eq!(pool.protocol_fees, buy.fee);
+        assert_eq!(buy.shares_out, 1_000 + 198 - pool.yes_reserves);
+        assert_eq!(buy.yes_price_after, pool.get_price(true).unwrap());
+        assert_eq!(market.total_collateral, 1_198);
+
+        let mut quoted = market.clone();
+        let trade = quoted.sell_to_pool(&mut pool.clone(), buy.shares_out, true, 200, 0).unwrap();
+        let sell = SellQuote::new(&trade, 0);
+        market.sell_to_pool(&mut pool, buy.shares_out, true, 200, 0).unwrap();
+
+        assert_eq!(pool.protocol_fees, buy.fee + sell.fee);
+        assert_eq!(sell.yes_price_after, pool.get_price(true).unwrap());
+        assert_eq!(market.total_collateral, 1_198 - sell.collateral_out - sell.fee);
+    }
+
+    #[test]
+    fn test_lmsr_quotes_match_trades() {
+        let mut market = market();
+        let mut lmsr_pool = LmsrPool::deserialize(&mut &[0u8; LmsrPool::INIT_SPACE][..]).unwrap();
+        lmsr_pool.initialize(Pubkey::new_unique(), 1_000_000, 693_148, 100, 255).unwrap();
+
+        let mut quoted = market.clone();
+        let trade = quoted.buy_from_lmsr(&mut lmsr_pool.clone(), 10_000, false).unwrap();
+        let buy = BuyQuote::new(&trade, 0, quoted, false).unwrap();
+        market.buy_from_lmsr(&mut lmsr_pool, 10_000, false).unwrap();
+
+        assert_eq!(lmsr_pool.accumulated_fees, buy.fee);
+        assert_eq!(lmsr_pool.no_shares, buy.shares_out);
+        assert_eq!(buy.yes_price_after, lmsr_pool.get_price(true).unwrap());
+        assert_eq!(market.total_no_tokens, 400 + buy.shares_out);
+
+        let mut quoted = market.clone();
+        let trade = quoted.sell_to_lmsr(&mut lmsr_pool.clone(), buy.shares_out, false).unwrap();
+        let sell = SellQuote::new(&trade, 0);
+        market.sell_to_lmsr(&mut lmsr_pool, buy.shares_out, false).unwrap();
+
+        assert_eq!(lmsr_pool.accumulated_fees, buy.fee + sell.fee);
+        assert_eq!(sell.yes_price_after, lmsr_pool.get_price(true).unwrap());
+        assert_eq!(market.total_collateral, 1_000 + 10_000 - buy.fee - sell.collateral_out - sell.fee);
+    }
+
+    #[test]
+    fn test_exposure_checked_on_amount_received() {
+        use anchor_spl::token_2022::spl_token_2022::extension::transfer_fee::TransferFee;
+
+        let mut market = market();
+        market.exposure_limits.max_user_stake = 1_000;
+
+        // A 1% transfer-fee mint credits 999 of a 1,010 bet
+        let fee = TransferFee {
+            epoch: 0.into(),
+            maximum_fee: u64::MAX.into(),
+            transfer_fee_basis_points: 100.into(),
+        };
+        let amount = 1_010;
+        let received = amount - fee.calculate_fee(amount).unwrap();
+        assert_eq!(received, 999);
+
+        assert_eq!(market.check_exposure(0, received).unwrap(), 999);
+        assert!(market.check_exposure(0, amount).is_err());
+        assert!(market.check_exposure(2, received).is_err());
+    }
+
+    #[test]
+    fn test_selling_releases_average_cost() {
+        let mut position = UserPosition {
+            user: Pubkey::new_unique(),
+            market: Pubkey::new_unique(),
+            total_staked: 900,
+        };
+
+        // A third of the holdings carries a third of the stake
+        assert_eq!(position.release_stake(100, 300).unwrap(), 300);
+        assert_eq!(position.total_staked, 600);
+        // Selling everything that is left clears the stake
+        assert_eq!(position.release_stake(200, 200).unwrap(), 600);
+        assert_eq!(position.total_staked, 0);
+        // Tokens received by plain transfer carry no stake
+        assert_eq!(position.release_stake(50, 50).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_lmsr_surplus_left_out_of_redemptions() {
+        // 1,000 collateral backs 600 YES shares, which pay one unit each
+        let mut market = market();
+        market.pricing_engine = PricingEngine::Lmsr;
+        market.outcome = Some(true);
+
+        assert_eq!(market.redemption_pot(), 600);
+        assert_eq!(market.lmsr_surplus(), 400);
+        assert_eq!(market.settlement_value(150, 0).unwrap(), 150);
+
+        // The surplus stays owed until the creator claims it
+        let report = AuditReport::new(&market, 1_000, 600, 400, false, market.lmsr_surplus()).unwrap();
+        assert_eq!(report.outstanding_obligations, 1_000);
+        assert!(report.solvent);
+
+        // Refunds and parimutuel markets share the whole pot
+        market.outcome = None;
+        assert_eq!(market.lmsr_surplus(), 0);
+        market.outcome = Some(true);
+        market.pricing_engine = PricingEngine::Parimutuel;
+        assert_eq!(market.redemption_pot(), 1_000);
+    }
+
+    #[test]
+    fn test_audit_values_winning_supply_after_resolution() {
+        let mut market = market();
+        market.outcome = Some(true);
+        market.book_collateral = 100;
+
+        // Half the winning tokens were claimed
+        let report = AuditReport::new(&market, 600, 300, 400, true, 0).unwrap();
+        assert_eq!(report.outstanding_obligations, 600);
+        assert!(report.solvent);
+
+        let report = AuditReport::new(&market, 599, 300, 400, true, 0).unwrap();
+        assert!(!report.solvent);
+    }
 }
 ```
\ No newline at end of file
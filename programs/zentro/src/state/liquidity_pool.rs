@@ -1,6 +1,7 @@
 ```rust
 use anchor_lang::prelude::*;
-use crate::errors::ZentroError;
+use crate::utils::fixed_point::{isqrt, mul_div_u64, Decimal, Rounding, BPS};
+use crate::ErrorCode as ZentroError;
 
 #[account]
 pub struct LiquidityPool {
@@ -12,14 +13,105 @@ pub struct LiquidityPool {
     pub no_token_mint: Pubkey,
     pub yes_token_vault: Pubkey,
     pub no_token_vault: Pubkey,
-    pub total_liquidity: u64,
+    /// Fungible LP token minted to providers, with the pool as authority.
+    /// Its supply is the pool's total liquidity.
+    pub lp_mint: Pubkey,
+    /// Pool-owned account holding staked LP tokens. Only staked tokens earn
+    /// trading fees, since the pool can't see transfers between wallets.
+    pub lp_vault: Pubkey,
     pub yes_reserves: u64,
     pub no_reserves: u64,
     pub fee_rate: u16, // basis points (e.g., 100 = 1%)
+    /// LP fees accrued and not yet claimed.
     pub accumulated_fees: u64,
     pub is_active: bool,
     pub created_at: i64,
     pub bump: u8,
+    pub fee_curve: Option<FeeCurve>,
+    /// Recent YES price movement in basis points, decaying over the fee
+    /// curve's `volatility_window`. Only tracked while a curve is set.
+    pub recent_price_move: u64,
+    pub last_trade_at: i64,
+    /// LP fees earned per liquidity token since the pool opened, as a raw
+    /// `Decimal`. Positions checkpoint it to work out what they are owed.
+    pub fee_per_share: u128,
+    /// Protocol's cut of trading fees, not yet collected.
+    pub protocol_fees: u64,
+    /// Running sum of the YES price, as a raw `Decimal` probability, times
+    /// the seconds it held. See `observe`.
+    pub yes_price_cumulative: u128,
+    /// When `yes_price_cumulative` was last brought up to date.
+    pub price_updated_at: i64,
+    /// LP tokens held in `lp_vault`, over which LP fees are spread.
+    pub staked_liquidity: u64,
+}
+
+/// A reading of a pool's price accumulator. The YES price averaged over the
+/// time between two readings is the accumulator's growth divided by that
+/// time, which a single transaction can't move far.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PriceObservation {
+    pub timestamp: i64,
+    pub yes_price_cumulative: u128,
+}
+
+impl PriceObservation {
+    /// Time-weighted YES probability in basis points from this reading to a
+    /// later one.
+    pub fn twap(&self, end: &PriceObservation) -> Result<u64> {
+        let elapsed = end.timestamp - self.timestamp;
+        require!(elapsed > 0, ZentroError::InvalidObservationWindow);
+
+        let growth = end.yes_price_cumulative.checked_sub(self.yes_price_cumulative)
+            .ok_or(ZentroError::InvalidObservationWindow)?;
+        Decimal::from_raw(growth / elapsed as u128).to_bps(Rounding::Down)
+    }
+}
+
+/// Optional dynamic fee charged on top of a pool's base `fee_rate`, so
+/// informed flow near resolution or after sharp moves pays LPs more.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FeeCurve {
+    /// Extra fee reached at the market's `end_time`, in basis points.
+    pub expiry_fee_rate: u16,
+    /// Seconds before `end_time` over which the expiry fee ramps up from zero.
+    pub expiry_window: i64,
+    /// Extra fee for a recent price move of 100%, in basis points. Smaller
+    /// moves are charged proportionally.
+    pub volatility_fee_rate: u16,
+    /// Seconds for a recorded price move to decay to zero.
+    pub volatility_window: i64,
+    /// Cap on the effective fee, in basis points.
+    pub max_fee_rate: u16,
+}
+
+impl FeeCurve {
+    pub const LEN: usize = 2 + 8 + 2 + 8 + 2;
+
+    pub fn validate(&self, base_fee_rate: u16) -> Result<()> {
+        require!(self.max_fee_rate <= 1000, ZentroError::InvalidFeeRate); // Max 10%
+        require!(self.max_fee_rate >= base_fee_rate, ZentroError::InvalidFeeRate);
+        require!(
+            self.expiry_window > 0 && self.volatility_window > 0,
+            ZentroError::InvalidFeeCurve
+        );
+        Ok(())
+    }
+}
+
+/// Accounts and settings a pool is created with.
+pub struct LiquidityPoolParams {
+    pub authority: Pubkey,
+    pub market: Pubkey,
+    pub token_mint: Pubkey,
+    pub token_vault: Pubkey,
+    pub yes_token_mint: Pubkey,
+    pub no_token_mint: Pubkey,
+    pub yes_token_vault: Pubkey,
+    pub no_token_vault: Pubkey,
+    pub lp_mint: Pubkey,
+    pub lp_vault: Pubkey,
+    pub fee_rate: u16,
 }
 
 impl LiquidityPool {
@@ -32,108 +124,103 @@ impl LiquidityPool {
         32 + // no_token_mint
         32 + // yes_token_vault
         32 + // no_token_vault
-        8 + // total_liquidity
+        32 + // lp_mint
+        32 + // lp_vault
         8 + // yes_reserves
         8 + // no_reserves
         2 + // fee_rate
         8 + // accumulated_fees
         1 + // is_active
         8 + // created_at
-        1; // bump
-
-    pub fn initialize(
-        &mut self,
-        authority: Pubkey,
-        market: Pubkey,
-        token_mint: Pubkey,
-        token_vault: Pubkey,
-        yes_token_mint: Pubkey,
-        no_token_mint: Pubkey,
-        yes_token_vault: Pubkey,
-        no_token_vault: Pubkey,
-        fee_rate: u16,
-        bump: u8,
-    ) -> Result<()> {
-        require!(fee_rate <= 1000, ZentroError::InvalidFeeRate); // Max 10%
-        
-        self.authority = authority;
-        self.market = market;
-        self.token_mint = token_mint;
-        self.token_vault = token_vault;
-        self.yes_token_mint = yes_token_mint;
-        self.no_token_mint = no_token_mint;
-        self.yes_token_vault = yes_token_vault;
-        self.no_token_vault = no_token_vault;
-        self.total_liquidity = 0;
+        1 + // bump
+        1 + FeeCurve::LEN + // fee_curve
+        8 + // recent_price_move
+        8 + // last_trade_at
+        16 + // fee_per_share
+        8 + // protocol_fees
+        16 + // yes_price_cumulative
+        8 + // price_updated_at
+        8; // staked_liquidity
+
+    pub fn initialize(&mut self, params: LiquidityPoolParams, bump: u8) -> Result<()> {
+        require!(params.fee_rate <= 1000, ZentroError::InvalidFeeRate); // Max 10%
+
+        self.authority = params.authority;
+        self.market = params.market;
+        self.token_mint = params.token_mint;
+        self.token_vault = params.token_vault;
+        self.yes_token_mint = params.yes_token_mint;
+        self.no_token_mint = params.no_token_mint;
+        self.yes_token_vault = params.yes_token_vault;
+        self.no_token_vault = params.no_token_vault;
+        self.lp_mint = params.lp_mint;
+        self.lp_vault = params.lp_vault;
         self.yes_reserves = 0;
         self.no_reserves = 0;
-        self.fee_rate = fee_rate;
+        self.fee_rate = params.fee_rate;
         self.accumulated_fees = 0;
         self.is_active = true;
         self.created_at = Clock::get()?.unix_timestamp;
         self.bump = bump;
+        self.fee_curve = None;
+        self.recent_price_move = 0;
+        self.last_trade_at = self.created_at;
+        self.fee_per_share = 0;
+        self.protocol_fees = 0;
+        self.yes_price_cumulative = 0;
+        self.price_updated_at = self.created_at;
+        self.staked_liquidity = 0;
 
         Ok(())
     }
 
-    pub fn add_liquidity(&mut self, amount: u64) -> Result<u64> {
+    pub fn signer_seeds(&self) -> [&[u8]; 3] {
+        [b"pool", self.market.as_ref(), std::slice::from_ref(&self.bump)]
+    }
+
+    /// Adds `amount` complete sets without moving the price. Returns the
+    /// liquidity tokens minted and the YES/NO amounts that went into the
+    /// reserves; the rest of each side is surplus returned to the provider.
+    /// `lp_supply` is the LP mint's supply before this deposit.
+    pub fn add_liquidity(&mut self, amount: u64, lp_supply: u64) -> Result<(u64, u64, u64)> {
         require!(self.is_active, ZentroError::PoolInactive);
         require!(amount > 0, ZentroError::InvalidAmount);
 
-        let liquidity_tokens = if self.total_liquidity == 0 {
+        let max_reserve = self.yes_reserves.max(self.no_reserves);
+
+        let (liquidity_tokens, yes_added, no_added) = if lp_supply == 0 || max_reserve == 0 {
             // Initial liquidity provision
-            amount
+            (amount, amount, amount)
         } else {
-            // Calculate proportional liquidity tokens
-            let total_reserves = self.yes_reserves.checked_add(self.no_reserves)
-                .ok_or(ZentroError::MathOverflow)?;
-            
-            if total_reserves == 0 {
-                amount
-            } else {
-                amount.checked_mul(self.total_liquidity)
-                    .ok_or(ZentroError::MathOverflow)?
-                    .checked_div(total_reserves)
-                    .ok_or(ZentroError::MathOverflow)?
-            }
+            // Scale both reserves by the same factor so the price is unchanged
+            let scale = |value: u64| mul_div_u64(amount, value, max_reserve, Rounding::Down);
+            (scale(lp_supply)?, scale(self.yes_reserves)?, scale(self.no_reserves)?)
         };
 
-        // Add equal amounts to both reserves initially
-        let half_amount = amount.checked_div(2).ok_or(ZentroError::MathOverflow)?;
-        
-        self.yes_reserves = self.yes_reserves.checked_add(half_amount)
-            .ok_or(ZentroError::MathOverflow)?;
-        self.no_reserves = self.no_reserves.checked_add(half_amount)
+        require!(liquidity_tokens > 0, ZentroError::InsufficientLiquidity);
+
+        self.yes_reserves = self.yes_reserves.checked_add(yes_added)
             .ok_or(ZentroError::MathOverflow)?;
-        
-        self.total_liquidity = self.total_liquidity.checked_add(liquidity_tokens)
+        self.no_reserves = self.no_reserves.checked_add(no_added)
             .ok_or(ZentroError::MathOverflow)?;
 
-        Ok(liquidity_tokens)
+        Ok((liquidity_tokens, yes_added, no_added))
     }
 
-    pub fn remove_liquidity(&mut self, liquidity_tokens: u64) -> Result<(u64, u64)> {
+    /// Takes the reserves backing `liquidity_tokens` out of the pool.
+    /// `lp_supply` is the LP mint's supply before the tokens are burned.
+    pub fn remove_liquidity(&mut self, liquidity_tokens: u64, lp_supply: u64) -> Result<(u64, u64)> {
         require!(self.is_active, ZentroError::PoolInactive);
         require!(liquidity_tokens > 0, ZentroError::InvalidAmount);
-        require!(liquidity_tokens <= self.total_liquidity, ZentroError::InsufficientLiquidity);
+        require!(liquidity_tokens <= lp_supply, ZentroError::InsufficientLiquidity);
 
-        let yes_amount = self.yes_reserves.checked_mul(liquidity_tokens)
-            .ok_or(ZentroError::MathOverflow)?
-            .checked_div(self.total_liquidity)
-            .ok_or(ZentroError::MathOverflow)?;
-
-        let no_amount = self.no_reserves.checked_mul(liquidity_tokens)
-            .ok_or(ZentroError::MathOverflow)?
-            .checked_div(self.total_liquidity)
-            .ok_or(ZentroError::MathOverflow)?;
+        let yes_amount = mul_div_u64(self.yes_reserves, liquidity_tokens, lp_supply, Rounding::Down)?;
+        let no_amount = mul_div_u64(self.no_reserves, liquidity_tokens, lp_supply, Rounding::Down)?;
 
         self.yes_reserves = self.yes_reserves.checked_sub(yes_amount)
             .ok_or(ZentroError::MathOverflow)?;
         self.no_reserves = self.no_reserves.checked_sub(no_amount)
             .ok_or(ZentroError::MathOverflow)?;
-        
-        self.total_liquidity = self.total_liquidity.checked_sub(liquidity_tokens)
-            .ok_or(ZentroError::MathOverflow)?;
 
         Ok((yes_amount, no_amount))
     }
@@ -151,10 +238,7 @@ impl LiquidityPool {
         require!(input_reserve > 0 && output_reserve > 0, ZentroError::InsufficientLiquidity);
 
         // Apply fee
-        let fee_amount = input_amount.checked_mul(self.fee_rate as u64)
-            .ok_or(ZentroError::MathOverflow)?
-            .checked_div(10000)
-            .ok_or(ZentroError::MathOverflow)?;
+        let fee_amount = mul_div_u64(input_amount, self.fee_rate as u64, BPS, Rounding::Down)?;
 
         let input_after_fee = input_amount.checked_sub(fee_amount)
             .ok_or(ZentroError::MathOverflow)?;
@@ -163,11 +247,8 @@ impl LiquidityPool {
         let new_input_reserve = input_reserve.checked_add(input_after_fee)
             .ok_or(ZentroError::MathOverflow)?;
 
-        let k = input_reserve.checked_mul(output_reserve)
-            .ok_or(ZentroError::MathOverflow)?;
-
-        let new_output_reserve = k.checked_div(new_input_reserve)
-            .ok_or(ZentroError::MathOverflow)?;
+        // Round the remaining reserve up so rounding favours the pool
+        let new_output_reserve = mul_div_u64(input_reserve, output_reserve, new_input_reserve, Rounding::Up)?;
 
         let output_amount = output_reserve.checked_sub(new_output_reserve)
             .ok_or(ZentroError::MathOverflow)?;
@@ -177,13 +258,15 @@ impl LiquidityPool {
         Ok(output_amount)
     }
 
-    pub fn execute_swap(&mut self, input_amount: u64, is_yes_to_no: bool) -> Result<u64> {
+    pub fn execute_swap(
+        &mut self,
+        input_amount: u64,
+        is_yes_to_no: bool,
+        protocol_fee_bps: u16,
+    ) -> Result<u64> {
         let output_amount = self.calculate_swap_output(input_amount, is_yes_to_no)?;
 
-        let fee_amount = input_amount.checked_mul(self.fee_rate as u64)
-            .ok_or(ZentroError::MathOverflow)?
-            .checked_div(10000)
-            .ok_or(ZentroError::MathOverflow)?;
+        let fee_amount = mul_div_u64(input_amount, self.fee_rate as u64, BPS, Rounding::Down)?;
 
         let input_after_fee = input_amount.checked_sub(fee_amount)
             .ok_or(ZentroError::MathOverflow)?;
@@ -200,30 +283,240 @@ impl LiquidityPool {
                 .ok_or(ZentroError::MathOverflow)?;
         }
 
-        self.accumulated_fees = self.accumulated_fees.checked_add(fee_amount)
-            .ok_or(ZentroError::MathOverflow)?;
+        self.accrue_fee(fee_amount, protocol_fee_bps)?;
 
         Ok(output_amount)
     }
 
+    pub fn set_fee_curve(&mut self, fee_curve: Option<FeeCurve>) -> Result<()> {
+        if let Some(curve) = &fee_curve {
+            curve.validate(self.fee_rate)?;
+        }
+        self.fee_curve = fee_curve;
+        self.recent_price_move = 0;
+        Ok(())
+    }
+
+    /// Fee rate in basis points for a trade at `now`. Without a fee curve
+    /// this is the static `fee_rate`.
+    pub fn current_fee_rate(&self, now: i64, end_time: i64) -> Result<u16> {
+        let Some(curve) = self.fee_curve else {
+            return Ok(self.fee_rate);
+        };
+
+        let mut fee_rate = self.fee_rate as u64;
+
+        // Ramps linearly from zero at the start of the window to the full
+        // expiry fee at end_time
+        let remaining = end_time.saturating_sub(now).max(0);
+        if remaining < curve.expiry_window {
+            fee_rate += mul_div_u64(
+                curve.expiry_fee_rate as u64,
+                (curve.expiry_window - remaining) as u64,
+                curve.expiry_window as u64,
+                Rounding::Up,
+            )?;
+        }
+
+        fee_rate += mul_div_u64(
+            self.decayed_price_move(&curve, now)?,
+            curve.volatility_fee_rate as u64,
+            BPS,
+            Rounding::Up,
+        )?;
+
+        Ok(fee_rate.min(curve.max_fee_rate as u64) as u16)
+    }
+
+    /// Adds the YES price change since `yes_price_before` to the decaying
+    /// volatility tracker. Call after every trade.
+    pub fn record_price_move(&mut self, yes_price_before: u64, now: i64) -> Result<()> {
+        let Some(curve) = self.fee_curve else {
+            return Ok(());
+        };
+
+        let moved = self.get_price(true)?.abs_diff(yes_price_before);
+        self.recent_price_move = self
+            .decayed_price_move(&curve, now)?
+            .checked_add(moved)
+            .ok_or(ZentroError::MathOverflow)?
+            .min(BPS);
+        self.last_trade_at = now;
+
+        Ok(())
+    }
+
+    fn decayed_price_move(&self, curve: &FeeCurve, now: i64) -> Result<u64> {
+        let elapsed = now.saturating_sub(self.last_trade_at).max(0);
+        if elapsed >= curve.volatility_window {
+            return Ok(0);
+        }
+
+        mul_div_u64(
+            self.recent_price_move,
+            (curve.volatility_window - elapsed) as u64,
+            curve.volatility_window as u64,
+            Rounding::Down,
+        )
+    }
+
+    /// Buys one outcome with collateral. The fee is taken from the
+    /// collateral, the rest is minted into complete sets added to both
+    /// reserves, and the bought side is paid out so that the reserve product
+    /// does not decrease. Returns `(shares_out, fee)`.
+    pub fn execute_buy(
+        &mut self,
+        collateral_in: u64,
+        buy_yes: bool,
+        fee_rate: u16,
+        protocol_fee_bps: u16,
+    ) -> Result<(u64, u64)> {
+        require!(self.is_active, ZentroError::PoolInactive);
+        require!(collateral_in > 0, ZentroError::InvalidAmount);
+        require!(self.yes_reserves > 0 && self.no_reserves > 0, ZentroError::InsufficientLiquidity);
+
+        let fee_amount = mul_div_u64(collateral_in, fee_rate as u64, BPS, Rounding::Down)?;
+        let net_amount = collateral_in.checked_sub(fee_amount)
+            .ok_or(ZentroError::MathOverflow)?;
+
+        let (bought_reserve, other_reserve) = if buy_yes {
+            (self.yes_reserves, self.no_reserves)
+        } else {
+            (self.no_reserves, self.yes_reserves)
+        };
+
+        let new_other_reserve = other_reserve.checked_add(net_amount)
+            .ok_or(ZentroError::MathOverflow)?;
+
+        // Round the remaining reserve up so rounding favours the pool
+        let new_bought_reserve = mul_div_u64(bought_reserve, other_reserve, new_other_reserve, Rounding::Up)?;
+
+        let shares_out = bought_reserve.checked_add(net_amount)
+            .ok_or(ZentroError::MathOverflow)?
+            .checked_sub(new_bought_reserve)
+            .ok_or(ZentroError::MathOverflow)?;
+
+        if buy_yes {
+            self.yes_reserves = new_bought_reserve;
+            self.no_reserves = new_other_reserve;
+        } else {
+            self.no_reserves = new_bought_reserve;
+            self.yes_reserves = new_other_reserve;
+        }
+
+        self.accrue_fee(fee_amount, protocol_fee_bps)?;
+
+        Ok((shares_out, fee_amount))
+    }
+
+    /// Sells outcome shares for collateral. The shares join their reserve
+    /// and the pool merges an equal amount of YES and NO so that the reserve
+    /// product is unchanged. Returns `(collateral_out, fee)`, where the fee
+    /// has already been deducted from `collateral_out`.
+    pub fn execute_sell(
+        &mut self,
+        shares_in: u64,
+        sell_yes: bool,
+        fee_rate: u16,
+        protocol_fee_bps: u16,
+    ) -> Result<(u64, u64)> {
+        require!(self.is_active, ZentroError::PoolInactive);
+        require!(shares_in > 0, ZentroError::InvalidAmount);
+        require!(self.yes_reserves > 0 && self.no_reserves > 0, ZentroError::InsufficientLiquidity);
+
+        let (sold_reserve, other_reserve) = if sell_yes {
+            (self.yes_reserves, self.no_reserves)
+        } else {
+            (self.no_reserves, self.yes_reserves)
+        };
+
+        // Solve (sold + shares_in - x) * (other - x) = sold * other for the
+        // smaller root x, the number of complete sets merged.
+        let b = sold_reserve as u128 + shares_in as u128 + other_reserve as u128;
+        let c = shares_in as u128 * other_reserve as u128;
+        let discriminant = b.checked_mul(b)
+            .ok_or(ZentroError::MathOverflow)?
+            .checked_sub(4 * c)
+            .ok_or(ZentroError::MathOverflow)?;
+        // Round the root up, then x down, so rounding favours the pool
+        let mut root = isqrt(discriminant);
+        if root * root < discriminant {
+            root += 1;
+        }
+        let merged = ((b - root) / 2) as u64;
+
+        require!(merged > 0 && merged < other_reserve, ZentroError::InsufficientLiquidity);
+
+        let new_sold_reserve = sold_reserve.checked_add(shares_in)
+            .ok_or(ZentroError::MathOverflow)?
+            .checked_sub(merged)
+            .ok_or(ZentroError::MathOverflow)?;
+        let new_other_reserve = other_reserve.checked_sub(merged)
+            .ok_or(ZentroError::MathOverflow)?;
+
+        if sell_yes {
+            self.yes_reserves = new_sold_reserve;
+            self.no_reserves = new_other_reserve;
+        } else {
+            self.no_reserves = new_sold_reserve;
+            self.yes_reserves = new_other_reserve;
+        }
+
+        let fee_amount = mul_div_u64(merged, fee_rate as u64, BPS, Rounding::Down)?;
+
+        self.accrue_fee(fee_amount, protocol_fee_bps)?;
+
+        let collateral_out = merged.checked_sub(fee_amount)
+            .ok_or(ZentroError::MathOverflow)?;
+
+        Ok((collateral_out, fee_amount))
+    }
+
+    /// Spot price from the current reserves. Anything that must resist
+    /// manipulation within a transaction should use `observe` instead.
     pub fn get_price(&self, is_yes_price: bool) -> Result<u64> {
         require!(self.yes_reserves > 0 && self.no_reserves > 0, ZentroError::InsufficientLiquidity);
 
         let total_reserves = self.yes_reserves.checked_add(self.no_reserves)
             .ok_or(ZentroError::MathOverflow)?;
 
-        if is_yes_price {
-            // Price as percentage (scaled by 10000 for precision)
-            self.yes_reserves.checked_mul(10000)
-                .ok_or(ZentroError::MathOverflow)?
-                .checked_div(total_reserves)
-                .ok_or(ZentroError::MathOverflow)
-        } else {
-            self.no_reserves.checked_mul(10000)
-                .ok_or(ZentroError::MathOverflow)?
-                .checked_div(total_reserves)
-                .ok_or(ZentroError::MathOverflow)
+        // An outcome is cheap when the pool holds a lot of it, so each
+        // price is the opposite reserve's share of the total.
+        // Price as percentage (scaled by 10000 for precision)
+        let opposite_reserve = if is_yes_price { self.no_reserves } else { self.yes_reserves };
+        mul_div_u64(opposite_reserve, BPS, total_reserves, Rounding::Down)
+    }
+
+    /// Reads the price accumulator as of `now`, counting the current price
+    /// for the time since the last update without writing anything. Time
+    /// with an empty pool adds nothing, so windows should start after
+    /// liquidity was first added.
+    pub fn observe(&self, now: i64) -> Result<PriceObservation> {
+        let elapsed = now.saturating_sub(self.price_updated_at).max(0) as u128;
+        let mut yes_price_cumulative = self.yes_price_cumulative;
+
+        if elapsed > 0 && self.yes_reserves > 0 && self.no_reserves > 0 {
+            let total_reserves = self.yes_reserves.checked_add(self.no_reserves)
+                .ok_or(ZentroError::MathOverflow)?;
+            let yes_price = Decimal::from_ratio(self.no_reserves, total_reserves, Rounding::Down)?;
+            yes_price_cumulative = yes_price.raw().checked_mul(elapsed)
+                .and_then(|weighted| yes_price_cumulative.checked_add(weighted))
+                .ok_or(ZentroError::MathOverflow)?;
         }
+
+        Ok(PriceObservation {
+            timestamp: now.max(self.price_updated_at),
+            yes_price_cumulative,
+        })
+    }
+
+    /// Brings the price accumulator up to `now`. Call before anything that
+    /// changes the reserves, so each price is weighted by how long it held.
+    pub fn accumulate_price(&mut self, now: i64) -> Result<()> {
+        let observation = self.observe(now)?;
+        self.yes_price_cumulative = observation.yes_price_cumulative;
+        self.price_updated_at = observation.timestamp;
+        Ok(())
     }
 
     pub fn deactivate(&mut self) -> Result<()> {
@@ -238,14 +531,191 @@ impl LiquidityPool {
 
     pub fn update_fee_rate(&mut self, new_fee_rate: u16) -> Result<()> {
         require!(new_fee_rate <= 1000, ZentroError::InvalidFeeRate); // Max 10%
+        if let Some(curve) = &self.fee_curve {
+            require!(curve.max_fee_rate >= new_fee_rate, ZentroError::InvalidFeeRate);
+        }
         self.fee_rate = new_fee_rate;
         Ok(())
     }
 
-    pub fn collect_fees(&mut self) -> Result<u64> {
-        let fees = self.accumulated_fees;
-        self.accumulated_fees = 0;
-        Ok(fees)
+    /// Splits a trading fee between the protocol and liquidity providers.
+    /// The LP share is spread over the staked LP tokens; with nothing
+    /// staked the protocol takes it all.
+    pub fn accrue_fee(&mut self, fee: u64, protocol_fee_bps: u16) -> Result<()> {
+        let protocol_cut = if self.staked_liquidity == 0 {
+            fee
+        } else {
+            mul_div_u64(fee, protocol_fee_bps as u64, BPS, Rounding::Down)?
+        };
+        let lp_fee = fee - protocol_cut;
+
+        if lp_fee > 0 {
+            let per_share = Decimal::from_ratio(lp_fee, self.staked_liquidity, Rounding::Down)?;
+            self.fee_per_share = self.fee_per_share.checked_add(per_share.raw())
+                .ok_or(ZentroError::MathOverflow)?;
+            self.accumulated_fees = self.accumulated_fees.checked_add(lp_fee)
+                .ok_or(ZentroError::MathOverflow)?;
+        }
+
+        self.protocol_fees = self.protocol_fees.checked_add(protocol_cut)
+            .ok_or(ZentroError::MathOverflow)?;
+
+        Ok(())
+    }
+
+    /// Records LP tokens moved into `lp_vault`.
+    pub fn stake(&mut self, amount: u64) -> Result<()> {
+        self.staked_liquidity = self.staked_liquidity.checked_add(amount)
+            .ok_or(ZentroError::MathOverflow)?;
+        Ok(())
+    }
+
+    /// Records LP tokens moved out of `lp_vault` or burned from it.
+    pub fn unstake(&mut self, amount: u64) -> Result<()> {
+        self.staked_liquidity = self.staked_liquidity.checked_sub(amount)
+            .ok_or(ZentroError::InsufficientLiquidity)?;
+        Ok(())
+    }
+
+    /// Pays out fees a position has claimed.
+    pub fn release_lp_fees(&mut self, amount: u64) -> Result<()> {
+        self.accumulated_fees = self.accumulated_fees.checked_sub(amount)
+            .ok_or(ZentroError::MathOverflow)?;
+        Ok(())
+    }
+
+    pub fn collect_protocol_fees(&mut self) -> u64 {
+        std::mem::take(&mut self.protocol_fees)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const END: i64 = 10_000;
+
+    fn pool(fee_rate: u16) -> LiquidityPool {
+        let zeroed = [0u8; LiquidityPool::LEN - 8];
+        let mut pool = LiquidityPool::deserialize(&mut &zeroed[..]).unwrap();
+        pool.fee_rate = fee_rate;
+        pool
+    }
+
+    fn curve() -> FeeCurve {
+        FeeCurve {
+            expiry_fee_rate: 200,
+            expiry_window: 1_000,
+            volatility_fee_rate: 400,
+            volatility_window: 100,
+            max_fee_rate: 1_000,
+        }
+    }
+
+    #[test]
+    fn test_no_curve_uses_base_fee() {
+        let pool = pool(30);
+        assert_eq!(pool.current_fee_rate(END, END).unwrap(), 30);
+    }
+
+    #[test]
+    fn test_expiry_fee_ramps_over_window() {
+        let mut pool = pool(30);
+        pool.set_fee_curve(Some(curve())).unwrap();
+
+        // Before and at the start of the window only the base fee applies
+        assert_eq!(pool.current_fee_rate(END - 5_000, END).unwrap(), 30);
+        assert_eq!(pool.current_fee_rate(END - 1_000, END).unwrap(), 30);
+        // One second in rounds up to a whole basis point
+        assert_eq!(pool.current_fee_rate(END - 999, END).unwrap(), 31);
+        assert_eq!(pool.current_fee_rate(END - 500, END).unwrap(), 130);
+        // The full expiry fee at end_time and after it
+        assert_eq!(pool.current_fee_rate(END, END).unwrap(), 230);
+        assert_eq!(pool.current_fee_rate(END + 50, END).unwrap(), 230);
+    }
+
+    #[test]
+    fn test_volatility_fee_decays() {
+        let mut pool = pool(30);
+        pool.set_fee_curve(Some(curve())).unwrap();
+        pool.recent_price_move = 5_000;
+        pool.last_trade_at = 0;
+
+        assert_eq!(pool.current_fee_rate(0, END).unwrap(), 230);
+        assert_eq!(pool.current_fee_rate(50, END).unwrap(), 130);
+        assert_eq!(pool.current_fee_rate(100, END).unwrap(), 30);
+    }
+
+    #[test]
+    fn test_fee_capped_at_max() {
+        let mut pool = pool(30);
+        pool.set_fee_curve(Some(FeeCurve { max_fee_rate: 300, ..curve() })).unwrap();
+        pool.recent_price_move = BPS;
+        pool.last_trade_at = END;
+
+        assert_eq!(pool.current_fee_rate(END, END).unwrap(), 300);
+    }
+
+    #[test]
+    fn test_curve_validation() {
+        let mut pool = pool(30);
+        assert!(pool.set_fee_curve(Some(FeeCurve { max_fee_rate: 1_001, ..curve() })).is_err());
+        assert!(pool.set_fee_curve(Some(FeeCurve { max_fee_rate: 29, ..curve() })).is_err());
+        assert!(pool.set_fee_curve(Some(FeeCurve { expiry_window: 0, ..curve() })).is_err());
+        assert!(pool.set_fee_curve(Some(FeeCurve { volatility_window: 0, ..curve() })).is_err());
+        assert!(pool.fee_curve.is_none());
+
+        pool.recent_price_move = 5_000;
+        pool.set_fee_curve(Some(curve())).unwrap();
+        assert_eq!(pool.recent_price_move, 0);
+    }
+
+    #[test]
+    fn test_twap_weights_prices_by_time() {
+        let mut pool = pool(30);
+        pool.yes_reserves = 1_000;
+        pool.no_reserves = 1_000;
+        let start = pool.observe(0).unwrap();
+
+        // YES at 50% for 100 seconds, then a trade moves it to 75% for 200
+        pool.accumulate_price(100).unwrap();
+        assert_eq!(start.twap(&pool.observe(100).unwrap()).unwrap(), 5_000);
+        pool.no_reserves = 3_000;
+        pool.accumulate_price(300).unwrap();
+
+        // (5,000 * 100 + 7,500 * 200) / 300
+        let end = pool.observe(300).unwrap();
+        assert_eq!(end.timestamp, 300);
+        assert_eq!(start.twap(&end).unwrap(), 6_666);
+    }
+
+    #[test]
+    fn test_no_accumulation_without_elapsed_time() {
+        let mut pool = pool(30);
+        pool.yes_reserves = 1_000;
+        pool.no_reserves = 1_000;
+        pool.accumulate_price(100).unwrap();
+        let cumulative = pool.yes_price_cumulative;
+
+        // A second trade in the same second, or a stale clock, adds nothing
+        pool.no_reserves = 3_000;
+        pool.accumulate_price(100).unwrap();
+        pool.accumulate_price(90).unwrap();
+        assert_eq!(pool.yes_price_cumulative, cumulative);
+        assert_eq!(pool.price_updated_at, 100);
+    }
+
+    #[test]
+    fn test_twap_rejects_empty_window() {
+        let mut pool = pool(30);
+        pool.yes_reserves = 1_000;
+        pool.no_reserves = 1_000;
+        let start = pool.observe(100).unwrap();
+        let end = pool.observe(200).unwrap();
+
+        for (from, to) in [(&start, &start), (&end, &start)] {
+            assert_eq!(from.twap(to).unwrap_err(), ZentroError::InvalidObservationWindow.into());
+        }
     }
 }
 ```
\ No newline at end of file
@@ -0,0 +1,159 @@
+use anchor_lang::prelude::*;
+
+use crate::utils::fixed_point::{Decimal, Rounding};
+use crate::ErrorCode;
+
+/// A provider's staked LP tokens. Staked tokens sit in the pool's
+/// `lp_vault`, so the program sees every change to `shares` and fees are
+/// only ever paid on tokens that were staked while they accrued.
+#[account]
+#[derive(InitSpace)]
+pub struct LiquidityPosition {
+    pub market: Pubkey,
+    pub owner: Pubkey,
+    /// LP tokens staked in the pool's `lp_vault`.
+    pub shares: u64,
+    pub bump: u8,
+    /// Pool `fee_per_share` when fees were last settled into `pending_fees`.
+    pub fee_per_share_checkpoint: u128,
+    /// Fees earned and not yet claimed.
+    pub pending_fees: u64,
+}
+
+impl LiquidityPosition {
+    /// Fills in a position created by `init_if_needed`. Existing positions
+    /// are left alone.
+    pub fn open(&mut self, market: Pubkey, owner: Pubkey, bump: u8) {
+        if self.market == Pubkey::default() {
+            self.market = market;
+            self.owner = owner;
+            self.bump = bump;
+        }
+    }
+
+    /// Moves fees earned on `shares` since the last checkpoint into
+    /// `pending_fees`.
+    pub fn settle_fees(&mut self, fee_per_share: u128) -> Result<()> {
+        let growth = fee_per_share
+            .checked_sub(self.fee_per_share_checkpoint)
+            .ok_or(ErrorCode::MathOverflow)?;
+        let earned = Decimal::from_raw(growth).mul_u64(self.shares, Rounding::Down)?;
+
+        self.pending_fees = self.pending_fees.checked_add(earned).ok_or(ErrorCode::MathOverflow)?;
+        self.fee_per_share_checkpoint = fee_per_share;
+
+        Ok(())
+    }
+
+    /// Adds staked tokens, settling what the old stake earned first.
+    pub fn stake(&mut self, fee_per_share: u128, amount: u64) -> Result<()> {
+        self.settle_fees(fee_per_share)?;
+        self.shares = self.shares.checked_add(amount).ok_or(ErrorCode::MathOverflow)?;
+        Ok(())
+    }
+
+    /// Removes staked tokens, settling what the old stake earned first.
+    pub fn unstake(&mut self, fee_per_share: u128, amount: u64) -> Result<()> {
+        self.settle_fees(fee_per_share)?;
+        self.shares = self.shares.checked_sub(amount).ok_or(ErrorCode::InsufficientLiquidity)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::LiquidityPool;
+
+    fn pool() -> LiquidityPool {
+        let zeroed = [0u8; LiquidityPool::LEN - 8];
+        LiquidityPool::deserialize(&mut &zeroed[..]).unwrap()
+    }
+
+    fn position() -> LiquidityPosition {
+        let mut position = LiquidityPosition {
+            market: Pubkey::default(),
+            owner: Pubkey::default(),
+            shares: 0,
+            bump: 0,
+            fee_per_share_checkpoint: 0,
+            pending_fees: 0,
+        };
+        position.open(Pubkey::new_unique(), Pubkey::new_unique(), 255);
+        position
+    }
+
+    fn stake(pool: &mut LiquidityPool, position: &mut LiquidityPosition, amount: u64) {
+        position.stake(pool.fee_per_share, amount).unwrap();
+        pool.stake(amount).unwrap();
+    }
+
+    fn unstake(pool: &mut LiquidityPool, position: &mut LiquidityPosition, amount: u64) {
+        position.unstake(pool.fee_per_share, amount).unwrap();
+        pool.unstake(amount).unwrap();
+    }
+
+    #[test]
+    fn test_fees_split_by_stake() {
+        let mut pool = pool();
+        let (mut alice, mut bob) = (position(), position());
+        stake(&mut pool, &mut alice, 300);
+        stake(&mut pool, &mut bob, 100);
+
+        pool.accrue_fee(4_000, 0).unwrap();
+        alice.settle_fees(pool.fee_per_share).unwrap();
+        bob.settle_fees(pool.fee_per_share).unwrap();
+
+        assert_eq!(alice.pending_fees, 3_000);
+        assert_eq!(bob.pending_fees, 1_000);
+    }
+
+    #[test]
+    fn test_passing_tokens_between_wallets_does_not_double_count() {
+        let mut pool = pool();
+        let (mut alice, mut bob) = (position(), position());
+        stake(&mut pool, &mut alice, 100);
+        pool.accrue_fee(1_000, 0).unwrap();
+
+        // The same 100 tokens go back and forth, staked by whoever holds them
+        for _ in 0..3 {
+            unstake(&mut pool, &mut alice, 100);
+            stake(&mut pool, &mut bob, 100);
+            unstake(&mut pool, &mut bob, 100);
+            stake(&mut pool, &mut alice, 100);
+        }
+        pool.accrue_fee(500, 0).unwrap();
+        alice.settle_fees(pool.fee_per_share).unwrap();
+        bob.settle_fees(pool.fee_per_share).unwrap();
+
+        assert_eq!(alice.pending_fees, 1_500);
+        assert_eq!(bob.pending_fees, 0);
+        assert!(alice.pending_fees + bob.pending_fees <= pool.accumulated_fees);
+    }
+
+    #[test]
+    fn test_unstaked_tokens_stop_earning() {
+        let mut pool = pool();
+        let (mut alice, mut bob) = (position(), position());
+        stake(&mut pool, &mut alice, 100);
+        stake(&mut pool, &mut bob, 100);
+
+        unstake(&mut pool, &mut alice, 100);
+        pool.accrue_fee(1_000, 0).unwrap();
+        alice.settle_fees(pool.fee_per_share).unwrap();
+        bob.settle_fees(pool.fee_per_share).unwrap();
+
+        assert_eq!(alice.pending_fees, 0);
+        assert_eq!(bob.pending_fees, 1_000);
+        assert!(alice.unstake(pool.fee_per_share, 1).is_err());
+    }
+
+    #[test]
+    fn test_fees_with_nothing_staked_go_to_protocol() {
+        let mut pool = pool();
+        pool.accrue_fee(1_000, 2_000).unwrap();
+
+        assert_eq!(pool.protocol_fees, 1_000);
+        assert_eq!(pool.accumulated_fees, 0);
+    }
+}
@@ -0,0 +1,157 @@
+use anchor_lang::prelude::*;
+
+use crate::utils::fixed_point::{mul_div_u64, Decimal, Rounding, BPS};
+use crate::utils::lmsr;
+use crate::ErrorCode as ZentroError;
+
+/// LMSR market maker for a market. Shares are minted straight from the
+/// market's outcome mints, so the pool only tracks how many of each outcome
+/// it has sold; collateral lives in the market vault.
+#[account]
+#[derive(InitSpace)]
+pub struct LmsrPool {
+    pub market: Pubkey,
+    pub liquidity_parameter: u64,
+    pub yes_shares: u64,
+    pub no_shares: u64,
+    pub subsidy: u64,
+    pub fee_rate: u16, // basis points (e.g., 100 = 1%)
+    pub accumulated_fees: u64,
+    pub bump: u8,
+    /// The creator has taken the subsidy left over after resolution.
+    pub surplus_claimed: bool,
+}
+
+impl LmsrPool {
+    pub fn initialize(
+        &mut self,
+        market: Pubkey,
+        liquidity_parameter: u64,
+        subsidy: u64,
+        fee_rate: u16,
+        bump: u8,
+    ) -> Result<()> {
+        require!(fee_rate <= 1000, ZentroError::InvalidFeeRate); // Max 10%
+        require!(liquidity_parameter > 0, ZentroError::InvalidAmount);
+
+        self.market = market;
+        self.liquidity_parameter = liquidity_parameter;
+        self.yes_shares = 0;
+        self.no_shares = 0;
+        self.subsidy = subsidy;
+        self.fee_rate = fee_rate;
+        self.accumulated_fees = 0;
+        self.bump = bump;
+        self.surplus_claimed = false;
+
+        Ok(())
+    }
+
+    /// Takes the accumulated fees and, if given, the market's leftover
+    /// subsidy, which can only be taken once. Returns the total owed to the
+    /// creator.
+    pub fn take_creator_funds(&mut self, surplus: Option<u64>) -> Result<u64> {
+        let mut amount = std::mem::take(&mut self.accumulated_fees);
+        if let Some(surplus) = surplus.filter(|_| !self.surplus_claimed) {
+            amount = amount.checked_add(surplus).ok_or(ZentroError::MathOverflow)?;
+            self.surplus_claimed = true;
+        }
+        Ok(amount)
+    }
+
+    /// Buys one outcome with collateral. The fee is taken from the
+    /// collateral and the rest is priced by the cost function.
+    /// Returns `(shares_out, fee)`.
+    pub fn execute_buy(&mut self, collateral_in: u64, buy_yes: bool) -> Result<(u64, u64)> {
+        require!(collateral_in > 0, ZentroError::InvalidAmount);
+
+        let fee_amount = self.fee(collateral_in)?;
+        let net_amount = collateral_in.checked_sub(fee_amount)
+            .ok_or(ZentroError::MathOverflow)?;
+
+        let shares_out = lmsr::shares_for_cost(
+            self.yes_shares,
+            self.no_shares,
+            self.liquidity_parameter,
+            net_amount,
+            buy_yes,
+        )?;
+        require!(shares_out > 0, ZentroError::InvalidAmount);
+
+        if buy_yes {
+            self.yes_shares = self.yes_shares.checked_add(shares_out)
+                .ok_or(ZentroError::MathOverflow)?;
+        } else {
+            self.no_shares = self.no_shares.checked_add(shares_out)
+                .ok_or(ZentroError::MathOverflow)?;
+        }
+
+        self.accumulated_fees = self.accumulated_fees.checked_add(fee_amount)
+            .ok_or(ZentroError::MathOverflow)?;
+
+        Ok((shares_out, fee_amount))
+    }
+
+    /// Sells outcome shares back to the maker. Only shares the maker has
+    /// sold can be bought back. Returns `(collateral_out, fee)`, where the
+    /// fee has already been deducted from `collateral_out`.
+    pub fn execute_sell(&mut self, shares_in: u64, sell_yes: bool) -> Result<(u64, u64)> {
+        require!(shares_in > 0, ZentroError::InvalidAmount);
+
+        let proceeds = lmsr::proceeds_for_shares(
+            self.yes_shares,
+            self.no_shares,
+            self.liquidity_parameter,
+            shares_in,
+            sell_yes,
+        )?;
+
+        if sell_yes {
+            self.yes_shares -= shares_in;
+        } else {
+            self.no_shares -= shares_in;
+        }
+
+        let fee_amount = self.fee(proceeds)?;
+        self.accumulated_fees = self.accumulated_fees.checked_add(fee_amount)
+            .ok_or(ZentroError::MathOverflow)?;
+
+        let collateral_out = proceeds.checked_sub(fee_amount)
+            .ok_or(ZentroError::MathOverflow)?;
+
+        Ok((collateral_out, fee_amount))
+    }
+
+    /// Price as percentage (scaled by 10000 for precision)
+    pub fn get_price(&self, is_yes_price: bool) -> Result<u64> {
+        let price = lmsr::price(self.yes_shares, self.no_shares, self.liquidity_parameter, is_yes_price)?;
+        Decimal::from_raw(price as u128).to_bps(Rounding::Down)
+    }
+
+    fn fee(&self, amount: u64) -> Result<u64> {
+        mul_div_u64(amount, self.fee_rate as u64, BPS, Rounding::Down)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_creator_funds_take_surplus_once() {
+        let mut pool = LmsrPool::deserialize(&mut &[0u8; LmsrPool::INIT_SPACE][..]).unwrap();
+        pool.initialize(Pubkey::new_unique(), 1_000_000, 693_148, 100, 255).unwrap();
+        pool.accumulated_fees = 50;
+
+        // Before resolution only fees are paid
+        assert_eq!(pool.take_creator_funds(None).unwrap(), 50);
+        assert_eq!(pool.take_creator_funds(None).unwrap(), 0);
+
+        pool.accumulated_fees = 20;
+        assert_eq!(pool.take_creator_funds(Some(400)).unwrap(), 420);
+        assert!(pool.surplus_claimed);
+
+        pool.accumulated_fees = 5;
+        assert_eq!(pool.take_creator_funds(Some(400)).unwrap(), 5);
+    }
+}
@@ -1 +1,15 @@
-I'm sorry. I'm not able to assist with that.
\ No newline at end of file
+pub mod liquidity_pool;
+pub mod liquidity_position;
+pub mod lmsr_pool;
+pub mod open_orders;
+pub mod order_book;
+pub mod price_history;
+pub mod signed_order;
+
+pub use liquidity_pool::*;
+pub use liquidity_position::*;
+pub use lmsr_pool::*;
+pub use open_orders::*;
+pub use order_book::*;
+pub use price_history::*;
+pub use signed_order::*;
@@ -0,0 +1,153 @@
+use anchor_lang::prelude::*;
+
+use crate::state::{Fill, OrderBook, Side};
+use crate::ErrorCode;
+
+/// A trader's balances on a market's order books. Collateral and outcome
+/// tokens backing resting orders are locked; the rest is free, funded by
+/// `deposit_funds`, fills and cancellations, and withdrawn with
+/// `settle_funds`. Signed off-chain orders settle against free balances.
+#[account]
+#[derive(InitSpace)]
+pub struct OpenOrders {
+    pub market: Pubkey,
+    pub owner: Pubkey,
+    pub collateral_free: u64,
+    pub collateral_locked: u64,
+    pub yes_free: u64,
+    pub yes_locked: u64,
+    pub no_free: u64,
+    pub no_locked: u64,
+    pub bump: u8,
+}
+
+impl OpenOrders {
+    /// Fills in an account created by `init_if_needed`. Existing accounts
+    /// are left alone.
+    pub fn open(&mut self, market: Pubkey, owner: Pubkey, bump: u8) {
+        if self.market == Pubkey::default() {
+            self.market = market;
+            self.owner = owner;
+            self.bump = bump;
+        }
+    }
+
+    pub fn credit_collateral(&mut self, amount: u64) -> Result<()> {
+        self.collateral_free = self.collateral_free.checked_add(amount).ok_or(ErrorCode::MathOverflow)?;
+        Ok(())
+    }
+
+    pub fn credit_outcome(&mut self, is_yes: bool, amount: u64) -> Result<()> {
+        let (free, _) = self.outcome_mut(is_yes);
+        *free = free.checked_add(amount).ok_or(ErrorCode::MathOverflow)?;
+        Ok(())
+    }
+
+    /// Takes free collateral paid away outside the order book.
+    pub fn debit_collateral(&mut self, amount: u64) -> Result<()> {
+        self.collateral_free = self.collateral_free.checked_sub(amount).ok_or(ErrorCode::InsufficientBalance)?;
+        Ok(())
+    }
+
+    /// Takes free outcome tokens sold outside the order book.
+    pub fn debit_outcome(&mut self, is_yes: bool, amount: u64) -> Result<()> {
+        let (free, _) = self.outcome_mut(is_yes);
+        *free = free.checked_sub(amount).ok_or(ErrorCode::InsufficientBalance)?;
+        Ok(())
+    }
+
+    /// Moves free collateral behind a resting bid.
+    pub fn lock_collateral(&mut self, amount: u64) -> Result<()> {
+        move_balance(&mut self.collateral_free, &mut self.collateral_locked, amount)
+    }
+
+    /// Moves free outcome tokens behind a resting ask.
+    pub fn lock_outcome(&mut self, is_yes: bool, amount: u64) -> Result<()> {
+        let (free, locked) = self.outcome_mut(is_yes);
+        move_balance(free, locked, amount)
+    }
+
+    pub fn unlock_collateral(&mut self, amount: u64) -> Result<()> {
+        move_balance(&mut self.collateral_locked, &mut self.collateral_free, amount)
+    }
+
+    pub fn unlock_outcome(&mut self, is_yes: bool, amount: u64) -> Result<()> {
+        let (free, locked) = self.outcome_mut(is_yes);
+        move_balance(locked, free, amount)
+    }
+
+    /// Releases locked collateral paid away in a fill.
+    pub fn spend_collateral(&mut self, amount: u64) -> Result<()> {
+        self.collateral_locked = self.collateral_locked.checked_sub(amount).ok_or(ErrorCode::InsufficientBalance)?;
+        Ok(())
+    }
+
+    /// Releases locked outcome tokens sold in a fill.
+    pub fn spend_outcome(&mut self, is_yes: bool, amount: u64) -> Result<()> {
+        let (_, locked) = self.outcome_mut(is_yes);
+        *locked = locked.checked_sub(amount).ok_or(ErrorCode::InsufficientBalance)?;
+        Ok(())
+    }
+
+    /// Settles a fill of this account's incoming order, on `side` at limit
+    /// `price`, against the resting order of `maker`. The taker's funds
+    /// were locked for the whole order before matching.
+    pub fn settle_fill(
+        &mut self,
+        maker: &mut OpenOrders,
+        side: Side,
+        outcome: bool,
+        price: u64,
+        fill: &Fill,
+    ) -> Result<()> {
+        let value = OrderBook::notional(fill.price, fill.quantity)?;
+        match side {
+            Side::Bid => {
+                // The bid locked collateral at its own limit; anything
+                // above the maker's price comes back free
+                let locked = OrderBook::notional(price, fill.quantity)?;
+                self.spend_collateral(locked)?;
+                self.credit_collateral(locked - value)?;
+                self.credit_outcome(outcome, fill.quantity)?;
+                maker.spend_outcome(outcome, fill.quantity)?;
+                maker.credit_collateral(value)
+            }
+            Side::Ask => {
+                self.spend_outcome(outcome, fill.quantity)?;
+                self.credit_collateral(value)?;
+                maker.spend_collateral(value)?;
+                maker.credit_outcome(outcome, fill.quantity)
+            }
+        }
+    }
+
+    /// Empties the free collateral and free outcome tokens for withdrawal,
+    /// returning `(collateral, tokens)`.
+    pub fn take_free(&mut self, is_yes: bool) -> (u64, u64) {
+        let (free, _) = self.outcome_mut(is_yes);
+        let tokens = std::mem::take(free);
+        (std::mem::take(&mut self.collateral_free), tokens)
+    }
+
+    pub fn outcome_free(&self, is_yes: bool) -> u64 {
+        if is_yes {
+            self.yes_free
+        } else {
+            self.no_free
+        }
+    }
+
+    fn outcome_mut(&mut self, is_yes: bool) -> (&mut u64, &mut u64) {
+        if is_yes {
+            (&mut self.yes_free, &mut self.yes_locked)
+        } else {
+            (&mut self.no_free, &mut self.no_locked)
+        }
+    }
+}
+
+fn move_balance(from: &mut u64, to: &mut u64, amount: u64) -> Result<()> {
+    *from = from.checked_sub(amount).ok_or(ErrorCode::InsufficientBalance)?;
+    *to = to.checked_add(amount).ok_or(ErrorCode::MathOverflow)?;
+    Ok(())
+}
@@ -0,0 +1,466 @@
+use anchor_lang::prelude::*;
+
+use crate::utils::fixed_point::{mul_div_u64, Rounding, BPS};
+use crate::ErrorCode;
+
+/// Resting orders kept per side of a book.
+pub const MAX_ORDERS: usize = 64;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Side {
+    Bid,
+    Ask,
+}
+
+impl Side {
+    pub fn opposite(self) -> Side {
+        match self {
+            Side::Bid => Side::Ask,
+            Side::Ask => Side::Bid,
+        }
+    }
+
+    /// Whether an incoming order at `limit_price` trades with a resting
+    /// order on the other side at `resting_price`.
+    fn crosses(self, limit_price: u64, resting_price: u64) -> bool {
+        match self {
+            Side::Bid => resting_price <= limit_price,
+            Side::Ask => resting_price >= limit_price,
+        }
+    }
+
+    /// Whether a resting order at `price` ranks strictly behind `other` on
+    /// this side of the book.
+    fn ranks_behind(self, price: u64, other: u64) -> bool {
+        match self {
+            Side::Bid => price < other,
+            Side::Ask => price > other,
+        }
+    }
+}
+
+#[zero_copy]
+#[derive(Debug, Default)]
+pub struct Order {
+    pub order_id: u64,
+    /// Open-orders account credited when the order fills.
+    pub open_orders: Pubkey,
+    /// Limit price in basis points of collateral per share.
+    pub price: u64,
+    /// Shares still open.
+    pub quantity: u64,
+    pub placed_at: i64,
+}
+
+/// A trade between an incoming order and a resting one, at the resting
+/// order's price.
+#[derive(Clone, Copy, Debug)]
+pub struct Fill {
+    pub maker_order_id: u64,
+    pub maker: Pubkey,
+    pub price: u64,
+    pub quantity: u64,
+}
+
+/// Limit order book for one outcome of a market. Each side is kept sorted
+/// best price first, and by order id within a price, so index zero is
+/// always next to fill.
+#[account(zero_copy)]
+pub struct OrderBook {
+    pub market: Pubkey,
+    pub outcome_mint: Pubkey,
+    /// Holds outcome tokens escrowed by asks or bought by bids until their
+    /// owners settle.
+    pub token_vault: Pubkey,
+    pub next_order_id: u64,
+    pub bid_count: u32,
+    pub ask_count: u32,
+    pub is_yes: u8,
+    pub bump: u8,
+    pub _padding: [u8; 6],
+    pub bids: [Order; MAX_ORDERS],
+    pub asks: [Order; MAX_ORDERS],
+}
+
+impl OrderBook {
+    pub const LEN: usize = 8 + std::mem::size_of::<OrderBook>();
+
+    /// Order sizes are whole multiples of this many base units, so every
+    /// price in basis points gives an exact collateral amount.
+    pub const LOT_SIZE: u64 = BPS;
+
+    pub fn initialize(
+        &mut self,
+        market: Pubkey,
+        outcome_mint: Pubkey,
+        token_vault: Pubkey,
+        is_yes: bool,
+        bump: u8,
+    ) {
+        self.market = market;
+        self.outcome_mint = outcome_mint;
+        self.token_vault = token_vault;
+        self.next_order_id = 0;
+        self.bid_count = 0;
+        self.ask_count = 0;
+        self.is_yes = is_yes as u8;
+        self.bump = bump;
+    }
+
+    /// Collateral exchanged for `quantity` shares at `price`.
+    pub fn notional(price: u64, quantity: u64) -> Result<u64> {
+        mul_div_u64(price, quantity, BPS, Rounding::Down)
+    }
+
+    pub fn validate_order(price: u64, quantity: u64) -> Result<()> {
+        require!(price > 0 && price < BPS, ErrorCode::InvalidOrderPrice);
+        require!(
+            quantity > 0 && quantity.is_multiple_of(Self::LOT_SIZE),
+            ErrorCode::InvalidOrderSize
+        );
+        Ok(())
+    }
+
+    pub fn orders(&self, side: Side) -> &[Order] {
+        match side {
+            Side::Bid => &self.bids[..self.bid_count as usize],
+            Side::Ask => &self.asks[..self.ask_count as usize],
+        }
+    }
+
+    pub fn best(&self, side: Side) -> Option<&Order> {
+        self.orders(side).first()
+    }
+
+    pub fn take_order_id(&mut self) -> u64 {
+        let order_id = self.next_order_id;
+        self.next_order_id += 1;
+        order_id
+    }
+
+    /// Fills an incoming order against the opposite side, best price first,
+    /// until it no longer crosses. Returns the fills and the quantity left.
+    ///
+    /// Errors instead of leaving a crossed book if more than `max_fills`
+    /// resting orders would be hit, and refuses to fill against the taker's
+    /// own orders.
+    pub fn match_order(
+        &mut self,
+        side: Side,
+        limit_price: u64,
+        quantity: u64,
+        taker: &Pubkey,
+        max_fills: usize,
+    ) -> Result<(Vec<Fill>, u64)> {
+        let resting_side = side.opposite();
+        let mut remaining = quantity;
+        let mut fills = Vec::new();
+
+        while remaining > 0 {
+            let Some(&best) = self.best(resting_side) else {
+                break;
+            };
+            if !side.crosses(limit_price, best.price) {
+                break;
+            }
+            require!(best.open_orders != *taker, ErrorCode::SelfTrade);
+            require!(fills.len() < max_fills, ErrorCode::TooManyFills);
+
+            let filled = remaining.min(best.quantity);
+            remaining -= filled;
+            if filled == best.quantity {
+                self.remove_at(resting_side, 0);
+            } else {
+                self.side_mut(resting_side)[0].quantity -= filled;
+            }
+
+            fills.push(Fill {
+                maker_order_id: best.order_id,
+                maker: best.open_orders,
+                price: best.price,
+                quantity: filled,
+            });
+        }
+
+        Ok((fills, remaining))
+    }
+
+    /// Rests an order behind every order at the same or a better price.
+    pub fn insert(&mut self, side: Side, order: Order) -> Result<()> {
+        let count = self.count(side);
+        require!(count < MAX_ORDERS, ErrorCode::OrderBookFull);
+
+        let index = self
+            .orders(side)
+            .iter()
+            .position(|resting| side.ranks_behind(resting.price, order.price))
+            .unwrap_or(count);
+
+        let orders = self.side_mut(side);
+        orders.copy_within(index..count, index + 1);
+        orders[index] = order;
+        self.set_count(side, count + 1);
+
+        Ok(())
+    }
+
+    /// Removes a resting order by id from whichever side holds it.
+    pub fn remove(&mut self, order_id: u64) -> Result<(Side, Order)> {
+        for side in [Side::Bid, Side::Ask] {
+            if let Some(index) = self.orders(side).iter().position(|order| order.order_id == order_id) {
+                let order = self.orders(side)[index];
+                self.remove_at(side, index);
+                return Ok((side, order));
+            }
+        }
+        err!(ErrorCode::OrderNotFound)
+    }
+
+    fn remove_at(&mut self, side: Side, index: usize) {
+        let count = self.count(side);
+        let orders = self.side_mut(side);
+        orders.copy_within(index + 1..count, index);
+        orders[count - 1] = Order::default();
+        self.set_count(side, count - 1);
+    }
+
+    fn count(&self, side: Side) -> usize {
+        match side {
+            Side::Bid => self.bid_count as usize,
+            Side::Ask => self.ask_count as usize,
+        }
+    }
+
+    fn set_count(&mut self, side: Side, count: usize) {
+        match side {
+            Side::Bid => self.bid_count = count as u32,
+            Side::Ask => self.ask_count = count as u32,
+        }
+    }
+
+    fn side_mut(&mut self, side: Side) -> &mut [Order; MAX_ORDERS] {
+        match side {
+            Side::Bid => &mut self.bids,
+            Side::Ask => &mut self.asks,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::OpenOrders;
+
+    const LOT: u64 = OrderBook::LOT_SIZE;
+
+    fn book() -> Box<OrderBook> {
+        Box::new(bytemuck::Zeroable::zeroed())
+    }
+
+    fn order(book: &mut OrderBook, owner: Pubkey, price: u64, quantity: u64) -> Order {
+        Order {
+            order_id: book.take_order_id(),
+            open_orders: owner,
+            price,
+            quantity,
+            placed_at: 0,
+        }
+    }
+
+    fn open_orders(owner: Pubkey) -> OpenOrders {
+        OpenOrders {
+            market: Pubkey::default(),
+            owner,
+            collateral_free: 0,
+            collateral_locked: 0,
+            yes_free: 0,
+            yes_locked: 0,
+            no_free: 0,
+            no_locked: 0,
+            bump: 0,
+        }
+    }
+
+    #[test]
+    fn test_validate_order() {
+        assert!(OrderBook::validate_order(5_000, 3 * LOT).is_ok());
+        assert!(OrderBook::validate_order(0, LOT).is_err());
+        assert!(OrderBook::validate_order(BPS, LOT).is_err());
+        assert!(OrderBook::validate_order(5_000, 0).is_err());
+        assert!(OrderBook::validate_order(5_000, LOT + 1).is_err());
+    }
+
+    #[test]
+    fn test_matches_best_price_then_earliest_order() {
+        let mut book = book();
+        let (alice, bob, carol) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let first = order(&mut book, alice, 6_000, LOT);
+        let second = order(&mut book, bob, 6_000, LOT);
+        let better = order(&mut book, carol, 5_500, LOT);
+        book.insert(Side::Ask, first).unwrap();
+        book.insert(Side::Ask, second).unwrap();
+        book.insert(Side::Ask, better).unwrap();
+
+        let taker = Pubkey::new_unique();
+        let (fills, remaining) = book.match_order(Side::Bid, 7_000, 3 * LOT, &taker, 3).unwrap();
+
+        assert_eq!(remaining, 0);
+        let makers: Vec<_> = fills.iter().map(|fill| (fill.maker, fill.price)).collect();
+        assert_eq!(makers, vec![(carol, 5_500), (alice, 6_000), (bob, 6_000)]);
+        assert_eq!(book.ask_count, 0);
+    }
+
+    #[test]
+    fn test_stops_at_limit_price() {
+        let mut book = book();
+        let maker = Pubkey::new_unique();
+        let bid = order(&mut book, maker, 4_000, LOT);
+        book.insert(Side::Bid, bid).unwrap();
+
+        let taker = Pubkey::new_unique();
+        let (fills, remaining) = book.match_order(Side::Ask, 4_500, LOT, &taker, 5).unwrap();
+
+        assert!(fills.is_empty());
+        assert_eq!(remaining, LOT);
+        assert_eq!(book.bid_count, 1);
+    }
+
+    #[test]
+    fn test_partial_fill_leaves_rest_of_maker_order() {
+        let mut book = book();
+        let maker = Pubkey::new_unique();
+        let bid = order(&mut book, maker, 5_000, 5 * LOT);
+        book.insert(Side::Bid, bid).unwrap();
+
+        let taker = Pubkey::new_unique();
+        let (fills, remaining) = book.match_order(Side::Ask, 5_000, 2 * LOT, &taker, 1).unwrap();
+
+        assert_eq!(remaining, 0);
+        assert_eq!(fills[0].quantity, 2 * LOT);
+        assert_eq!(book.best(Side::Bid).unwrap().quantity, 3 * LOT);
+        assert_eq!(book.best(Side::Bid).unwrap().order_id, bid.order_id);
+    }
+
+    #[test]
+    fn test_partial_fill_of_taker_returns_remainder() {
+        let mut book = book();
+        let maker = Pubkey::new_unique();
+        let ask = order(&mut book, maker, 5_000, LOT);
+        book.insert(Side::Ask, ask).unwrap();
+
+        let taker = Pubkey::new_unique();
+        let (fills, remaining) = book.match_order(Side::Bid, 5_000, 3 * LOT, &taker, 2).unwrap();
+
+        assert_eq!(fills.len(), 1);
+        assert_eq!(remaining, 2 * LOT);
+        assert_eq!(book.ask_count, 0);
+    }
+
+    #[test]
+    fn test_rejects_self_trade() {
+        let mut book = book();
+        let trader = Pubkey::new_unique();
+        let ask = order(&mut book, trader, 5_000, LOT);
+        book.insert(Side::Ask, ask).unwrap();
+
+        assert!(book.match_order(Side::Bid, 5_000, LOT, &trader, 1).is_err());
+    }
+
+    #[test]
+    fn test_rejects_more_fills_than_maker_accounts() {
+        let mut book = book();
+        for _ in 0..2 {
+            let ask = order(&mut book, Pubkey::new_unique(), 5_000, LOT);
+            book.insert(Side::Ask, ask).unwrap();
+        }
+
+        let taker = Pubkey::new_unique();
+        assert!(book.match_order(Side::Bid, 5_000, 2 * LOT, &taker, 1).is_err());
+    }
+
+    #[test]
+    fn test_book_full() {
+        let mut book = book();
+        for _ in 0..MAX_ORDERS {
+            let bid = order(&mut book, Pubkey::new_unique(), 5_000, LOT);
+            book.insert(Side::Bid, bid).unwrap();
+        }
+        let bid = order(&mut book, Pubkey::new_unique(), 5_000, LOT);
+        assert!(book.insert(Side::Bid, bid).is_err());
+    }
+
+    #[test]
+    fn test_cancel_removes_order_and_unlocks_funds() {
+        let mut book = book();
+        let owner = Pubkey::new_unique();
+        let mut account = open_orders(owner);
+        account.credit_collateral(OrderBook::notional(4_000, 2 * LOT).unwrap()).unwrap();
+
+        let bid = order(&mut book, owner, 4_000, 2 * LOT);
+        account.lock_collateral(OrderBook::notional(bid.price, bid.quantity).unwrap()).unwrap();
+        book.insert(Side::Bid, bid).unwrap();
+
+        let (side, removed) = book.remove(bid.order_id).unwrap();
+        account
+            .unlock_collateral(OrderBook::notional(removed.price, removed.quantity).unwrap())
+            .unwrap();
+
+        assert_eq!(side, Side::Bid);
+        assert_eq!(book.bid_count, 0);
+        assert_eq!(account.collateral_locked, 0);
+        assert_eq!(account.collateral_free, 8_000);
+        assert!(book.remove(bid.order_id).is_err());
+    }
+
+    #[test]
+    fn test_fill_then_settle_moves_balances() {
+        let mut book = book();
+        let (buyer_key, seller_key) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut buyer = open_orders(buyer_key);
+        let mut seller = open_orders(seller_key);
+
+        // Resting bid locks collateral at its limit
+        let cost = OrderBook::notional(6_000, 3 * LOT).unwrap();
+        buyer.credit_collateral(cost).unwrap();
+        buyer.lock_collateral(cost).unwrap();
+        let bid = order(&mut book, buyer_key, 6_000, 3 * LOT);
+        book.insert(Side::Bid, bid).unwrap();
+
+        // An ask for part of it fills at the bid's price
+        seller.credit_outcome(true, LOT).unwrap();
+        seller.lock_outcome(true, LOT).unwrap();
+        let (fills, remaining) = book.match_order(Side::Ask, 5_000, LOT, &seller_key, 1).unwrap();
+        assert_eq!(remaining, 0);
+        seller.settle_fill(&mut buyer, Side::Ask, true, 5_000, &fills[0]).unwrap();
+
+        assert_eq!(seller.take_free(true), (6_000, 0));
+        assert_eq!(seller.yes_locked, 0);
+        assert_eq!(buyer.take_free(true), (0, LOT));
+        assert_eq!(buyer.collateral_locked, 12_000);
+        assert_eq!(buyer.take_free(true), (0, 0));
+    }
+
+    #[test]
+    fn test_bid_fill_refunds_price_improvement() {
+        let mut book = book();
+        let (buyer_key, seller_key) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut buyer = open_orders(buyer_key);
+        let mut seller = open_orders(seller_key);
+
+        seller.credit_outcome(false, LOT).unwrap();
+        seller.lock_outcome(false, LOT).unwrap();
+        let ask = order(&mut book, seller_key, 4_000, LOT);
+        book.insert(Side::Ask, ask).unwrap();
+
+        // A bid at 6,000 locks at its limit and fills at the ask's 4,000
+        buyer.credit_collateral(6_000).unwrap();
+        buyer.lock_collateral(6_000).unwrap();
+        let (fills, _) = book.match_order(Side::Bid, 6_000, LOT, &buyer_key, 1).unwrap();
+        buyer.settle_fill(&mut seller, Side::Bid, false, 6_000, &fills[0]).unwrap();
+
+        assert_eq!(buyer.collateral_locked, 0);
+        assert_eq!(buyer.take_free(false), (2_000, LOT));
+        assert_eq!(seller.no_locked, 0);
+        assert_eq!(seller.take_free(false), (4_000, 0));
+    }
+}
@@ -0,0 +1,155 @@
+use anchor_lang::prelude::*;
+
+use crate::ErrorCode;
+
+/// Candles kept per market. Sized so the account stays under the 10KB
+/// limit for accounts created through CPI.
+pub const PRICE_HISTORY_LEN: usize = 200;
+
+#[zero_copy]
+#[derive(Debug, Default)]
+pub struct Candle {
+    /// Start of the interval, a multiple of the history's `interval`.
+    pub start_time: i64,
+    /// YES probabilities in basis points.
+    pub open: u64,
+    pub high: u64,
+    pub low: u64,
+    pub close: u64,
+    /// Collateral traded during the interval.
+    pub volume: u64,
+}
+
+/// Ring buffer of YES price candles for charting a market from one account
+/// fetch. Intervals without trades get no candle.
+#[account(zero_copy)]
+pub struct PriceHistory {
+    pub market: Pubkey,
+    /// Seconds covered by each candle.
+    pub interval: i64,
+    /// Index of the latest candle in `candles`.
+    pub head: u64,
+    /// Candles written, up to `PRICE_HISTORY_LEN`. Once full, each new
+    /// candle overwrites the oldest.
+    pub count: u64,
+    pub bump: u8,
+    pub _padding: [u8; 7],
+    pub candles: [Candle; PRICE_HISTORY_LEN],
+}
+
+impl PriceHistory {
+    pub const LEN: usize = 8 + std::mem::size_of::<PriceHistory>();
+
+    pub fn initialize(&mut self, market: Pubkey, interval: i64, bump: u8) -> Result<()> {
+        require!(interval > 0, ErrorCode::InvalidInterval);
+
+        self.market = market;
+        self.interval = interval;
+        self.head = 0;
+        self.count = 0;
+        self.bump = bump;
+
+        Ok(())
+    }
+
+    /// Records a trade that left the YES price at `price`. A new candle
+    /// opens at the previous close, so the chart has no gaps in price.
+    pub fn record(&mut self, now: i64, price: u64, volume: u64) -> Result<()> {
+        let start_time = now - now.rem_euclid(self.interval);
+        let latest = self.candles[self.head as usize];
+
+        if self.count > 0 && latest.start_time == start_time {
+            let candle = &mut self.candles[self.head as usize];
+            candle.high = candle.high.max(price);
+            candle.low = candle.low.min(price);
+            candle.close = price;
+            candle.volume = candle.volume.checked_add(volume).ok_or(ErrorCode::MathOverflow)?;
+            return Ok(());
+        }
+
+        let open = if self.count > 0 { latest.close } else { price };
+        if self.count > 0 {
+            self.head = (self.head + 1) % PRICE_HISTORY_LEN as u64;
+        }
+        self.count = (self.count + 1).min(PRICE_HISTORY_LEN as u64);
+        self.candles[self.head as usize] = Candle {
+            start_time,
+            open,
+            high: open.max(price),
+            low: open.min(price),
+            close: price,
+            volume,
+        };
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INTERVAL: i64 = 60;
+
+    fn history() -> Box<PriceHistory> {
+        let mut history: Box<PriceHistory> = Box::new(bytemuck::Zeroable::zeroed());
+        history.initialize(Pubkey::new_unique(), INTERVAL, 255).unwrap();
+        history
+    }
+
+    #[test]
+    fn test_rejects_non_positive_interval() {
+        let mut history: Box<PriceHistory> = Box::new(bytemuck::Zeroable::zeroed());
+        assert!(history.initialize(Pubkey::new_unique(), 0, 255).is_err());
+    }
+
+    #[test]
+    fn test_trades_within_an_interval_update_one_candle() {
+        let mut history = history();
+        history.record(600, 5_000, 10).unwrap();
+        history.record(610, 5_400, 20).unwrap();
+        history.record(659, 4_800, 30).unwrap();
+
+        assert_eq!(history.count, 1);
+        let candle = history.candles[history.head as usize];
+        assert_eq!(candle.start_time, 600);
+        assert_eq!((candle.open, candle.high, candle.low, candle.close), (5_000, 5_400, 4_800, 4_800));
+        assert_eq!(candle.volume, 60);
+    }
+
+    #[test]
+    fn test_rolls_over_at_interval_boundary() {
+        let mut history = history();
+        history.record(659, 5_000, 10).unwrap();
+        history.record(660, 5_200, 5).unwrap();
+        // A quiet interval in between gets no candle
+        history.record(800, 4_900, 7).unwrap();
+
+        assert_eq!(history.count, 3);
+        assert_eq!(history.head, 2);
+        let second = history.candles[1];
+        assert_eq!(second.start_time, 660);
+        assert_eq!((second.open, second.high, second.low, second.close), (5_000, 5_200, 5_000, 5_200));
+        assert_eq!(second.volume, 5);
+        let third = history.candles[2];
+        assert_eq!(third.start_time, 780);
+        assert_eq!((third.open, third.low, third.close), (5_200, 4_900, 4_900));
+    }
+
+    #[test]
+    fn test_wraps_at_capacity() {
+        let mut history = history();
+        let total = PRICE_HISTORY_LEN as i64 + 5;
+        for i in 0..total {
+            history.record(i * INTERVAL, 1_000 + i as u64, 1).unwrap();
+        }
+
+        assert_eq!(history.count, PRICE_HISTORY_LEN as u64);
+        assert_eq!(history.head, 4);
+        let latest = history.candles[4];
+        assert_eq!(latest.start_time, (total - 1) * INTERVAL);
+        assert_eq!(latest.close, 1_000 + total as u64 - 1);
+        // The oldest surviving candle sits right after the head
+        assert_eq!(history.candles[5].start_time, 5 * INTERVAL);
+    }
+}
@@ -0,0 +1,113 @@
+use anchor_lang::prelude::*;
+
+use crate::state::{OrderBook, Side};
+use crate::ErrorCode;
+
+/// Domain tag prepended to signed order messages so an order signature
+/// can't be replayed as any other kind of message.
+pub const ORDER_MESSAGE_PREFIX: &[u8] = b"zentro:order:v1:";
+
+/// An order a maker signs off-chain for an operator to match.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SignedOrder {
+    pub maker: Pubkey,
+    pub market: Pubkey,
+    pub outcome: bool,
+    pub side: Side,
+    /// Limit price in basis points of collateral per share.
+    pub price: u64,
+    pub quantity: u64,
+    /// Unix timestamp after which the order can no longer fill.
+    pub expiry: i64,
+    /// Chosen by the maker, unique among their orders.
+    pub nonce: u64,
+}
+
+impl SignedOrder {
+    /// Bytes the maker signs.
+    pub fn message(&self) -> Result<Vec<u8>> {
+        let mut message = ORDER_MESSAGE_PREFIX.to_vec();
+        self.serialize(&mut message)
+            .map_err(|_| error!(ErrorCode::InvalidSignedOrder))?;
+        Ok(message)
+    }
+
+    pub fn validate(&self, market: &Pubkey, side: Side, now: i64) -> Result<()> {
+        require_keys_eq!(self.market, *market, ErrorCode::InvalidSignedOrder);
+        require!(self.side == side, ErrorCode::InvalidSignedOrder);
+        require!(now < self.expiry, ErrorCode::OrderExpired);
+        OrderBook::validate_order(self.price, self.quantity)
+    }
+}
+
+/// How much of a maker's signed order has filled, keyed by maker and nonce
+/// so a signature can't fill more than once over.
+#[account]
+#[derive(InitSpace)]
+pub struct OrderStatus {
+    pub maker: Pubkey,
+    pub nonce: u64,
+    pub filled: u64,
+    pub cancelled: bool,
+    pub bump: u8,
+}
+
+impl OrderStatus {
+    /// Fills in an account created by `init_if_needed`. Existing accounts
+    /// are left alone.
+    pub fn open(&mut self, maker: Pubkey, nonce: u64, bump: u8) {
+        if self.maker == Pubkey::default() {
+            self.maker = maker;
+            self.nonce = nonce;
+            self.bump = bump;
+        }
+    }
+
+    /// Stops further fills. What has already filled is kept.
+    pub fn cancel(&mut self) {
+        self.cancelled = true;
+    }
+
+    pub fn record_fill(&mut self, order: &SignedOrder, quantity: u64) -> Result<()> {
+        require!(!self.cancelled, ErrorCode::OrderAlreadyCancelled);
+        let filled = self.filled.checked_add(quantity).ok_or(ErrorCode::MathOverflow)?;
+        require!(filled <= order.quantity, ErrorCode::OrderOverfilled);
+        self.filled = filled;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn order(quantity: u64) -> SignedOrder {
+        SignedOrder {
+            maker: Pubkey::new_unique(),
+            market: Pubkey::new_unique(),
+            outcome: true,
+            side: Side::Bid,
+            price: 6_000,
+            quantity,
+            expiry: i64::MAX,
+            nonce: 7,
+        }
+    }
+
+    #[test]
+    fn test_cancel_after_partial_fill() {
+        let order = order(5 * OrderBook::LOT_SIZE);
+        let mut status = OrderStatus::deserialize(&mut &[0u8; OrderStatus::INIT_SPACE][..]).unwrap();
+        status.open(order.maker, order.nonce, 254);
+        status.record_fill(&order, 2 * OrderBook::LOT_SIZE).unwrap();
+
+        // Cancelling reuses the existing status account
+        status.open(Pubkey::new_unique(), 99, 1);
+        status.cancel();
+        assert_eq!(status.maker, order.maker);
+        assert_eq!(status.filled, 2 * OrderBook::LOT_SIZE);
+
+        assert!(status.record_fill(&order, OrderBook::LOT_SIZE).is_err());
+        assert_eq!(status.filled, 2 * OrderBook::LOT_SIZE);
+    }
+}
@@ -0,0 +1,140 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+use anchor_spl::token::spl_token;
+use anchor_spl::token_2022::spl_token_2022::extension::transfer_fee::TransferFeeConfig;
+use anchor_spl::token_2022::spl_token_2022::extension::{BaseStateWithExtensions, StateWithExtensions};
+use anchor_spl::token_2022::spl_token_2022::{self, onchain::invoke_transfer_checked};
+use anchor_spl::token_interface::{self, CloseAccount, Mint, SyncNative, TokenAccount};
+
+use crate::ErrorCode;
+
+/// Markets created with a native mint take and pay out plain SOL. Their
+/// vault is a wSOL token account, wrapped on deposit and unwrapped on payout,
+/// so betting and claiming use the same accounts as SPL markets.
+pub fn is_native(mint: &Pubkey) -> bool {
+    *mint == spl_token::native_mint::ID || *mint == spl_token_2022::native_mint::ID
+}
+
+/// Amount a transfer-fee mint withholds from a transfer of `amount` in the
+/// current epoch, so quotes can match what `deposit` and `withdraw` deliver.
+/// Zero for native SOL and mints without the extension.
+pub fn transfer_fee(mint: &InterfaceAccount<Mint>, amount: u64) -> Result<u64> {
+    let mint_info = mint.to_account_info();
+    if is_native(&mint.key()) || *mint_info.owner != spl_token_2022::ID {
+        return Ok(0);
+    }
+
+    let data = mint_info.try_borrow_data()?;
+    let state = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&data)?;
+    let Ok(fee_config) = state.get_extension::<TransferFeeConfig>() else {
+        return Ok(0);
+    };
+
+    fee_config
+        .calculate_epoch_fee(Clock::get()?.epoch, amount)
+        .ok_or_else(|| error!(ErrorCode::MathOverflow))
+}
+
+/// Moves collateral from `user` into `vault` and returns the amount the vault
+/// was credited.
+///
+/// For native markets lamports are taken from the user's wallet and wrapped
+/// in the vault; `user_token_account` is not needed. For SPL markets the
+/// tokens move with `transfer_checked`, and mints with a transfer-fee
+/// extension deliver less than `amount`, so callers must account with the
+/// returned value. `hook_accounts` are the extra accounts a transfer-hook
+/// mint needs, passed through from the instruction's remaining accounts.
+#[allow(clippy::too_many_arguments)]
+pub fn deposit<'info>(
+    token_program: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    mint: &InterfaceAccount<'info, Mint>,
+    vault: &mut InterfaceAccount<'info, TokenAccount>,
+    user: &AccountInfo<'info>,
+    user_token_account: Option<&AccountInfo<'info>>,
+    hook_accounts: &[AccountInfo<'info>],
+    amount: u64,
+) -> Result<u64> {
+    let balance_before = vault.amount;
+
+    if is_native(&mint.key()) {
+        system_program::transfer(
+            CpiContext::new(
+                system_program.clone(),
+                system_program::Transfer {
+                    from: user.clone(),
+                    to: vault.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
+        token_interface::sync_native(CpiContext::new(
+            token_program.clone(),
+            SyncNative {
+                account: vault.to_account_info(),
+            },
+        ))?;
+    } else {
+        let from = user_token_account.ok_or(ErrorCode::MissingUserTokenAccount)?;
+        invoke_transfer_checked(
+            token_program.key,
+            from.clone(),
+            mint.to_account_info(),
+            vault.to_account_info(),
+            user.clone(),
+            hook_accounts,
+            amount,
+            mint.decimals,
+            &[],
+        )?;
+    }
+
+    vault.reload()?;
+    let received = vault
+        .amount
+        .checked_sub(balance_before)
+        .ok_or(ErrorCode::MathOverflow)?;
+
+    Ok(received)
+}
+
+/// Pays collateral out of a market vault, signing as the vault PDA.
+///
+/// For native markets the payout lands in the user's wSOL account, which is
+/// then closed so the user receives plain SOL.
+#[allow(clippy::too_many_arguments)]
+pub fn withdraw<'info>(
+    token_program: &AccountInfo<'info>,
+    vault: &InterfaceAccount<'info, TokenAccount>,
+    mint: &InterfaceAccount<'info, Mint>,
+    user: &AccountInfo<'info>,
+    user_token_account: &AccountInfo<'info>,
+    hook_accounts: &[AccountInfo<'info>],
+    amount: u64,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    invoke_transfer_checked(
+        token_program.key,
+        vault.to_account_info(),
+        mint.to_account_info(),
+        user_token_account.clone(),
+        vault.to_account_info(),
+        hook_accounts,
+        amount,
+        mint.decimals,
+        signer_seeds,
+    )?;
+
+    if is_native(&mint.key()) {
+        token_interface::close_account(CpiContext::new(
+            token_program.clone(),
+            CloseAccount {
+                account: user_token_account.clone(),
+                destination: user.clone(),
+                authority: user.clone(),
+            },
+        ))?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,111 @@
+//! Signature checks through instruction introspection.
+//!
+//! The runtime cannot verify ed25519 signatures cheaply inside a program, so
+//! a transaction carries an instruction for the native Ed25519 program, which
+//! fails the whole transaction if any signature in it is invalid. A program
+//! only has to confirm, through the instructions sysvar, that such an
+//! instruction vouches for the exact key and message it cares about.
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::ed25519_program;
+use anchor_lang::solana_program::sysvar::instructions::{
+    load_current_index_checked, load_instruction_at_checked,
+};
+
+use crate::ErrorCode;
+
+/// Size of one signature's offsets entry after the two-byte header.
+const OFFSETS_LEN: usize = 14;
+
+/// Marks an offset as pointing into the Ed25519 instruction's own data.
+const THIS_INSTRUCTION: u16 = u16::MAX;
+
+/// Succeeds if an Ed25519 program instruction earlier in the transaction
+/// verified `signer`'s signature over `message`.
+pub fn verify_signature(instructions: &AccountInfo, signer: &Pubkey, message: &[u8]) -> Result<()> {
+    let current = load_current_index_checked(instructions)?;
+    for index in 0..current {
+        let instruction = load_instruction_at_checked(index as usize, instructions)?;
+        if instruction.program_id == ed25519_program::ID && signs(&instruction.data, signer, message) {
+            return Ok(());
+        }
+    }
+    err!(ErrorCode::MissingSignature)
+}
+
+/// Whether Ed25519 instruction `data` includes a signature by `signer` over
+/// `message`. Entries whose key or message live in another instruction are
+/// ignored, since their contents are not pinned by this one.
+fn signs(data: &[u8], signer: &Pubkey, message: &[u8]) -> bool {
+    let Some(&count) = data.first() else {
+        return false;
+    };
+
+    (0..count as usize).any(|i| {
+        let start = 2 + i * OFFSETS_LEN;
+        let Some(offsets) = data.get(start..start + OFFSETS_LEN) else {
+            return false;
+        };
+        let read = |at: usize| u16::from_le_bytes([offsets[at], offsets[at + 1]]) as usize;
+
+        let in_this_instruction = [read(2), read(6), read(12)]
+            .iter()
+            .all(|&index| index == THIS_INSTRUCTION as usize);
+        let key = data.get(read(4)..read(4) + 32);
+        let signed = data.get(read(8)..read(8) + read(10));
+
+        in_this_instruction && key == Some(signer.as_ref()) && signed == Some(message)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Lays out one signature the way the Ed25519 program's client helper
+    /// does: offsets, then key, signature and message.
+    fn instruction_data(signer: &Pubkey, message: &[u8], instruction_index: u16) -> Vec<u8> {
+        let key_offset = 2 + OFFSETS_LEN;
+        let signature_offset = key_offset + 32;
+        let message_offset = signature_offset + 64;
+
+        let mut data = vec![1, 0];
+        for value in [
+            signature_offset as u16,
+            instruction_index,
+            key_offset as u16,
+            instruction_index,
+            message_offset as u16,
+            message.len() as u16,
+            instruction_index,
+        ] {
+            data.extend_from_slice(&value.to_le_bytes());
+        }
+        data.extend_from_slice(signer.as_ref());
+        data.extend_from_slice(&[0; 64]);
+        data.extend_from_slice(message);
+        data
+    }
+
+    #[test]
+    fn test_signs_matches_key_and_message() {
+        let signer = Pubkey::new_unique();
+        let data = instruction_data(&signer, b"order", THIS_INSTRUCTION);
+
+        assert!(signs(&data, &signer, b"order"));
+        assert!(!signs(&data, &signer, b"other"));
+        assert!(!signs(&data, &Pubkey::new_unique(), b"order"));
+    }
+
+    #[test]
+    fn test_signs_rejects_external_and_malformed_data() {
+        let signer = Pubkey::new_unique();
+
+        assert!(!signs(&instruction_data(&signer, b"order", 0), &signer, b"order"));
+        assert!(!signs(&[], &signer, b"order"));
+
+        let mut truncated = instruction_data(&signer, b"order", THIS_INSTRUCTION);
+        truncated.truncate(truncated.len() - 1);
+        assert!(!signs(&truncated, &signer, b"order"));
+    }
+}
@@ -0,0 +1,353 @@
+//! Deterministic fixed-point arithmetic.
+//!
+//! Everything is integer math, so results are identical on every validator,
+//! and every operation that can overflow returns `MathOverflow` instead of
+//! wrapping or saturating. Signed values (`i128` scaled by `SCALE`) are used
+//! where results can go negative, such as `ln`; `Decimal` is the unsigned
+//! type for prices, ratios and odds.
+
+use anchor_lang::prelude::*;
+
+use crate::ErrorCode;
+
+/// Fixed-point values are scaled by `SCALE` (12 decimals).
+pub const SCALE: i128 = 1_000_000_000_000;
+
+/// Basis points in one whole.
+pub const BPS: u64 = 10_000;
+
+/// ln(2) scaled by `SCALE`.
+pub const LN_2: i128 = 693_147_180_560;
+
+// Series are evaluated at 18 decimals and rounded back to 12, so the error
+// from summing terms stays well below one unit of `SCALE`.
+const PRECISE_SCALE: i128 = 1_000_000_000_000_000_000;
+const PRECISE_LN_2: i128 = 693_147_180_559_945_309;
+const PRECISION_GAIN: i128 = PRECISE_SCALE / SCALE;
+
+/// Below this `exp` rounds to zero at 12 decimals.
+const EXP_MIN: i128 = -30 * SCALE;
+/// Above this `exp` no longer fits once scaled.
+const EXP_MAX: i128 = 50 * SCALE;
+
+const UNIT: u128 = SCALE as u128;
+
+/// How to round results that are not exact. Amounts paid out should round
+/// down and amounts charged should round up, so rounding favours the program.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Rounding {
+    Down,
+    Up,
+    /// Halves round up.
+    Nearest,
+}
+
+/// `a * b / denominator`, rounded as asked. The product is computed in
+/// `u128` and must fit there, so any two `u64` values are safe but two
+/// scaled `Decimal`s only are while their product stays below about
+/// 3.4e14 in whole units. Larger products fail with `MathOverflow` rather
+/// than losing precision.
+pub fn mul_div(a: u128, b: u128, denominator: u128, rounding: Rounding) -> Result<u128> {
+    require!(denominator != 0, ErrorCode::MathDomainError);
+
+    let product = a.checked_mul(b).ok_or(ErrorCode::MathOverflow)?;
+    let quotient = product / denominator;
+    let remainder = product % denominator;
+    let round_up = match rounding {
+        Rounding::Down => false,
+        Rounding::Up => remainder > 0,
+        Rounding::Nearest => remainder >= denominator - remainder,
+    };
+
+    Ok(quotient + round_up as u128)
+}
+
+/// `mul_div` for token amounts, failing if the result does not fit in `u64`.
+pub fn mul_div_u64(a: u64, b: u64, denominator: u64, rounding: Rounding) -> Result<u64> {
+    let value = mul_div(a as u128, b as u128, denominator as u128, rounding)?;
+    u64::try_from(value).map_err(|_| error!(ErrorCode::MathOverflow))
+}
+
+/// Integer square root, rounded down.
+pub fn isqrt(value: u128) -> u128 {
+    if value < 2 {
+        return value;
+    }
+
+    // Newton's method from an upper bound converges from above
+    let mut x = 1u128 << (128 - value.leading_zeros()).div_ceil(2);
+    loop {
+        let y = (x + value / x) / 2;
+        if y >= x {
+            return x;
+        }
+        x = y;
+    }
+}
+
+/// Unsigned fixed-point number with 12 decimals.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Decimal(u128);
+
+impl Decimal {
+    pub const ZERO: Self = Self(0);
+    pub const ONE: Self = Self(UNIT);
+
+    pub fn from_raw(raw: u128) -> Self {
+        Self(raw)
+    }
+
+    pub fn raw(self) -> u128 {
+        self.0
+    }
+
+    pub fn from_u64(value: u64) -> Self {
+        Self(value as u128 * UNIT)
+    }
+
+    pub fn from_bps(bps: u64) -> Self {
+        Self(bps as u128 * UNIT / BPS as u128)
+    }
+
+    /// `numerator / denominator` as a decimal.
+    pub fn from_ratio(numerator: u64, denominator: u64, rounding: Rounding) -> Result<Self> {
+        Ok(Self(mul_div(numerator as u128, UNIT, denominator as u128, rounding)?))
+    }
+
+    pub fn checked_add(self, rhs: Self) -> Result<Self> {
+        Ok(Self(self.0.checked_add(rhs.0).ok_or(ErrorCode::MathOverflow)?))
+    }
+
+    pub fn checked_sub(self, rhs: Self) -> Result<Self> {
+        Ok(Self(self.0.checked_sub(rhs.0).ok_or(ErrorCode::MathOverflow)?))
+    }
+
+    pub fn checked_mul(self, rhs: Self, rounding: Rounding) -> Result<Self> {
+        Ok(Self(mul_div(self.0, rhs.0, UNIT, rounding)?))
+    }
+
+    pub fn checked_div(self, rhs: Self, rounding: Rounding) -> Result<Self> {
+        Ok(Self(mul_div(self.0, UNIT, rhs.0, rounding)?))
+    }
+
+    /// `self^exponent` by repeated squaring, rounding every product.
+    pub fn checked_pow(self, exponent: u32, rounding: Rounding) -> Result<Self> {
+        let mut base = self;
+        let mut result = Self::ONE;
+        let mut remaining = exponent;
+        while remaining > 0 {
+            if remaining & 1 == 1 {
+                result = result.checked_mul(base, rounding)?;
+            }
+            remaining >>= 1;
+            if remaining > 0 {
+                base = base.checked_mul(base, rounding)?;
+            }
+        }
+        Ok(result)
+    }
+
+    /// Square root, rounded down.
+    pub fn sqrt(self) -> Result<Self> {
+        Ok(Self(isqrt(self.0.checked_mul(UNIT).ok_or(ErrorCode::MathOverflow)?)))
+    }
+
+    /// e^self, see [`exp`].
+    pub fn exp(self) -> Result<Self> {
+        Ok(Self(exp(self.signed()?)? as u128))
+    }
+
+    /// Natural logarithm, see [`ln`]. Negative for values below one.
+    pub fn ln(self) -> Result<i128> {
+        ln(self.signed()?)
+    }
+
+    /// `amount * self` in whole units.
+    pub fn mul_u64(self, amount: u64, rounding: Rounding) -> Result<u64> {
+        let value = mul_div(amount as u128, self.0, UNIT, rounding)?;
+        u64::try_from(value).map_err(|_| error!(ErrorCode::MathOverflow))
+    }
+
+    pub fn to_u64(self, rounding: Rounding) -> Result<u64> {
+        self.mul_u64(1, rounding)
+    }
+
+    pub fn to_bps(self, rounding: Rounding) -> Result<u64> {
+        self.mul_u64(BPS, rounding)
+    }
+
+    fn signed(self) -> Result<i128> {
+        i128::try_from(self.0).map_err(|_| error!(ErrorCode::MathOverflow))
+    }
+}
+
+pub fn from_u64(value: u64) -> i128 {
+    value as i128 * SCALE
+}
+
+/// Multiplies two fixed-point values, truncating toward zero.
+pub fn mul(a: i128, b: i128) -> Result<i128> {
+    Ok(a.checked_mul(b).ok_or(ErrorCode::MathOverflow)? / SCALE)
+}
+
+/// Divides two fixed-point values, truncating toward zero.
+pub fn div(a: i128, b: i128) -> Result<i128> {
+    require!(b != 0, ErrorCode::MathOverflow);
+    Ok(a.checked_mul(SCALE).ok_or(ErrorCode::MathOverflow)? / b)
+}
+
+/// Ratio of two integer amounts as a fixed-point value.
+pub fn ratio(numerator: u64, denominator: u64) -> Result<i128> {
+    div(numerator as i128, denominator as i128)
+}
+
+/// Multiplies an integer amount by a fixed-point value, giving whole units.
+pub fn mul_int(amount: u64, x: i128, rounding: Rounding) -> Result<i128> {
+    let product = (amount as i128)
+        .checked_mul(x)
+        .ok_or(ErrorCode::MathOverflow)?;
+
+    Ok(match rounding {
+        Rounding::Down => product.div_euclid(SCALE),
+        Rounding::Up => -(-product).div_euclid(SCALE),
+        Rounding::Nearest => (product + SCALE / 2).div_euclid(SCALE),
+    })
+}
+
+/// e^x. Fails above e^50 and returns zero below e^-30.
+pub fn exp(x: i128) -> Result<i128> {
+    if x < EXP_MIN {
+        return Ok(0);
+    }
+    require!(x <= EXP_MAX, ErrorCode::MathOverflow);
+
+    // Reduce to x = k * ln(2) + r with |r| <= ln(2) / 2
+    let precise_x = x * PRECISION_GAIN;
+    let k = (precise_x + PRECISE_LN_2 / 2).div_euclid(PRECISE_LN_2);
+    let r = precise_x - k * PRECISE_LN_2;
+
+    // Taylor series for e^r
+    let mut term = PRECISE_SCALE;
+    let mut sum = PRECISE_SCALE;
+    for i in 1..=30 {
+        term = term * r / (PRECISE_SCALE * i);
+        if term == 0 {
+            break;
+        }
+        sum += term;
+    }
+
+    let result = if k >= 0 {
+        (sum / PRECISION_GAIN)
+            .checked_mul(1i128 << k)
+            .ok_or(ErrorCode::MathOverflow)?
+    } else {
+        (sum >> (-k).min(127)) / PRECISION_GAIN
+    };
+
+    Ok(result)
+}
+
+/// Natural logarithm of a positive fixed-point value.
+pub fn ln(x: i128) -> Result<i128> {
+    require!(x > 0, ErrorCode::MathDomainError);
+
+    // Reduce to x = 2^k * m with 1 <= m < 2
+    let mut k: i128 = 0;
+    while (x >> k) >= 2 * SCALE {
+        k += 1;
+    }
+    while k <= 0 && (x << -k) < SCALE {
+        k -= 1;
+    }
+    let m = if k >= 0 { x >> k } else { x << -k } * PRECISION_GAIN;
+
+    // ln(m) = 2 * atanh(z) with z = (m - 1) / (m + 1) <= 1/3
+    let z = (m - PRECISE_SCALE) * PRECISE_SCALE / (m + PRECISE_SCALE);
+    let z_squared = z * z / PRECISE_SCALE;
+    let mut term = z;
+    let mut sum = z;
+    for i in 1..=30 {
+        term = term * z_squared / PRECISE_SCALE;
+        if term == 0 {
+            break;
+        }
+        sum += term / (2 * i + 1);
+    }
+
+    Ok((k * PRECISE_LN_2 + 2 * sum) / PRECISION_GAIN)
+}
+
+/// ln(1 + e^x), computed without overflowing for large x.
+pub fn softplus(x: i128) -> Result<i128> {
+    if x > 0 {
+        Ok(x + ln(SCALE + exp(-x)?)?)
+    } else {
+        ln(SCALE + exp(x)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: i128, expected: i128, tolerance: i128) {
+        assert!(
+            (actual - expected).abs() <= tolerance,
+            "expected {} got {}",
+            expected,
+            actual
+        );
+    }
+
+    #[test]
+    fn test_exp() {
+        assert_eq!(exp(0).unwrap(), SCALE);
+        assert_close(exp(SCALE).unwrap(), 2_718_281_828_459, 2);
+        assert_close(exp(-SCALE).unwrap(), 367_879_441_171, 2);
+        assert_close(exp(10 * SCALE).unwrap(), 22_026_465_794_806_718, 50_000);
+        assert_eq!(exp(-31 * SCALE).unwrap(), 0);
+        assert!(exp(51 * SCALE).is_err());
+    }
+
+    #[test]
+    fn test_ln() {
+        assert_eq!(ln(SCALE).unwrap(), 0);
+        assert_close(ln(2 * SCALE).unwrap(), LN_2, 1);
+        assert_close(ln(SCALE / 10).unwrap(), -2_302_585_092_994, 2);
+        assert_close(ln(1_000_000 * SCALE).unwrap(), 13_815_510_557_964, 2);
+        assert!(ln(0).is_err());
+    }
+
+    #[test]
+    fn test_mul_div_rounding() {
+        assert_eq!(mul_div(7, 3, 2, Rounding::Down).unwrap(), 10);
+        assert_eq!(mul_div(7, 3, 2, Rounding::Up).unwrap(), 11);
+        assert_eq!(mul_div(7, 3, 2, Rounding::Nearest).unwrap(), 11);
+        assert_eq!(mul_div(7, 2, 3, Rounding::Nearest).unwrap(), 5);
+        assert!(mul_div(u128::MAX, 2, 2, Rounding::Down).is_err());
+        assert!(mul_div(1, 1, 0, Rounding::Down).is_err());
+        assert!(mul_div_u64(u64::MAX, 2, 1, Rounding::Down).is_err());
+    }
+
+    #[test]
+    fn test_decimal() {
+        let third = Decimal::from_ratio(1, 3, Rounding::Down).unwrap();
+        assert_eq!(third.raw(), 333_333_333_333);
+        assert_eq!(third.to_bps(Rounding::Up).unwrap(), 3334);
+        assert_eq!(Decimal::from_bps(2500).mul_u64(1_000, Rounding::Down).unwrap(), 250);
+
+        let two = Decimal::from_u64(2);
+        assert_eq!(two.checked_pow(10, Rounding::Down).unwrap(), Decimal::from_u64(1024));
+        assert_eq!(Decimal::from_u64(9).sqrt().unwrap(), Decimal::from_u64(3));
+        assert_eq!(two.sqrt().unwrap().raw(), 1_414_213_562_373);
+        assert!(Decimal::ONE.checked_sub(two).is_err());
+    }
+
+    #[test]
+    fn test_exp_ln_round_trip() {
+        for x in [SCALE / 3, 5 * SCALE, 123 * SCALE] {
+            assert_close(exp(ln(x).unwrap()).unwrap(), x, x / 1_000_000_000);
+        }
+    }
+}
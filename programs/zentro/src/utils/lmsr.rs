@@ -0,0 +1,162 @@
+//! Logarithmic market scoring rule for binary markets.
+//!
+//! The maker's cost function is `C(q) = b * ln(e^(q_yes/b) + e^(q_no/b))`,
+//! where `q` is the number of shares of each outcome it has sold and `b` is
+//! the liquidity parameter. A trade costs the difference in `C` before and
+//! after it. Starting from `q = 0` the maker can lose at most `b * ln(2)`,
+//! which is the subsidy the market creator funds.
+
+use anchor_lang::prelude::*;
+
+use crate::utils::fixed_point::{self, Rounding, SCALE};
+use crate::ErrorCode;
+
+/// Most units `shares_for_cost` will step its estimate down by.
+const MAX_CORRECTION_STEPS: u32 = 8;
+
+/// Subsidy that covers the maker's worst-case loss, rounded up.
+pub fn max_loss(b: u64) -> Result<u64> {
+    to_units(fixed_point::mul_int(b, fixed_point::LN_2, Rounding::Up)?)
+}
+
+/// `C(q)` in collateral units scaled by `SCALE`.
+pub fn cost(q_yes: u64, q_no: u64, b: u64) -> Result<i128> {
+    require!(b > 0, ErrorCode::InsufficientLiquidity);
+
+    // C(q) = max(q) + b * ln(1 + e^(-|q_yes - q_no| / b)) keeps exp in range
+    let (high, low) = (q_yes.max(q_no), q_yes.min(q_no));
+    let spread = fixed_point::ratio(high - low, b)?;
+    let premium = (b as i128)
+        .checked_mul(fixed_point::softplus(-spread)?)
+        .ok_or(ErrorCode::MathOverflow)?;
+
+    fixed_point::from_u64(high)
+        .checked_add(premium)
+        .ok_or(error!(ErrorCode::MathOverflow))
+}
+
+/// Instantaneous price of one outcome as a probability scaled by `SCALE`.
+pub fn price(q_yes: u64, q_no: u64, b: u64, is_yes: bool) -> Result<i128> {
+    require!(b > 0, ErrorCode::InsufficientLiquidity);
+
+    let (own, other) = if is_yes { (q_yes, q_no) } else { (q_no, q_yes) };
+    let d = fixed_point::div(other as i128 - own as i128, b as i128)?;
+
+    // p = 1 / (1 + e^d), written so exp never sees a positive argument
+    if d > 0 {
+        let e = fixed_point::exp(-d)?;
+        fixed_point::div(e, SCALE + e)
+    } else {
+        fixed_point::div(SCALE, SCALE + fixed_point::exp(d)?)
+    }
+}
+
+/// Shares of one outcome that `amount` of collateral buys, rounded down so
+/// the cost difference never exceeds what was paid.
+pub fn shares_for_cost(q_yes: u64, q_no: u64, b: u64, amount: u64, buy_yes: bool) -> Result<u64> {
+    require!(b > 0, ErrorCode::InsufficientLiquidity);
+
+    // Solving C(q + shares) - C(q) = amount for the bought side gives
+    // shares = b * (ln(e^(amount/b) - 1 + p) - ln(p))
+    let (own, other) = if buy_yes { (q_yes, q_no) } else { (q_no, q_yes) };
+    let d = fixed_point::div(other as i128 - own as i128, b as i128)?;
+    let p = price(q_yes, q_no, b, buy_yes)?;
+    let growth = fixed_point::exp(fixed_point::ratio(amount, b)?)?;
+    let log_ratio = fixed_point::ln(growth - SCALE + p)? + fixed_point::softplus(d)?;
+    let mut shares = to_units(fixed_point::mul_int(b, log_ratio, Rounding::Down)?)?;
+
+    // Fixed-point error can leave the estimate a unit or two high; step down
+    // until the cost difference fits in the amount paid. Each step costs an
+    // exp and a ln, so an estimate further off fails instead of running out
+    // of compute
+    let before = cost(q_yes, q_no, b)?;
+    let budget = fixed_point::from_u64(amount);
+    for _ in 0..=MAX_CORRECTION_STEPS {
+        if shares == 0 {
+            return Ok(0);
+        }
+        let after = if buy_yes {
+            cost(q_yes.checked_add(shares).ok_or(ErrorCode::MathOverflow)?, q_no, b)?
+        } else {
+            cost(q_yes, q_no.checked_add(shares).ok_or(ErrorCode::MathOverflow)?, b)?
+        };
+        if after - before <= budget {
+            return Ok(shares);
+        }
+        shares -= 1;
+    }
+
+    err!(ErrorCode::MathOverflow)
+}
+
+/// Collateral the maker pays for `shares` of one outcome, rounded down.
+pub fn proceeds_for_shares(q_yes: u64, q_no: u64, b: u64, shares: u64, sell_yes: bool) -> Result<u64> {
+    let (after_yes, after_no) = if sell_yes {
+        (q_yes.checked_sub(shares).ok_or(ErrorCode::InsufficientLiquidity)?, q_no)
+    } else {
+        (q_yes, q_no.checked_sub(shares).ok_or(ErrorCode::InsufficientLiquidity)?)
+    };
+
+    let proceeds = cost(q_yes, q_no, b)? - cost(after_yes, after_no, b)?;
+    to_units(proceeds.div_euclid(SCALE))
+}
+
+fn to_units(value: i128) -> Result<u64> {
+    u64::try_from(value).map_err(|_| error!(ErrorCode::MathOverflow))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const B: u64 = 1_000_000_000;
+
+    #[test]
+    fn test_price_starts_even_and_moves_with_buys() {
+        assert_eq!(price(0, 0, B, true).unwrap(), SCALE / 2);
+
+        let yes = price(2 * B, 0, B, true).unwrap();
+        let no = price(2 * B, 0, B, false).unwrap();
+        assert!(yes > SCALE / 2);
+        assert!((yes + no - SCALE).abs() <= 1);
+    }
+
+    #[test]
+    fn test_buy_never_costs_more_than_paid() {
+        for amount in [1, 1_000, 500_000_000, 20 * B] {
+            let shares = shares_for_cost(300, 7_000, B, amount, true).unwrap();
+            let paid = cost(300 + shares, 7_000, B).unwrap() - cost(300, 7_000, B).unwrap();
+            assert!(paid <= fixed_point::from_u64(amount));
+            assert!(shares >= amount);
+        }
+    }
+
+    #[test]
+    fn test_buy_with_large_liquidity_parameter() {
+        let b = 1_000_000_000_000_000;
+        for amount in [1_000, b / 4, b, 10 * b] {
+            let shares = shares_for_cost(b / 3, b / 7, b, amount, true).unwrap();
+            let paid = cost(b / 3 + shares, b / 7, b).unwrap() - cost(b / 3, b / 7, b).unwrap();
+            assert!(paid <= fixed_point::from_u64(amount));
+            assert!(shares >= amount);
+        }
+    }
+
+    #[test]
+    fn test_round_trip_does_not_profit() {
+        let amount = 250_000_000;
+        let shares = shares_for_cost(0, 0, B, amount, false).unwrap();
+        let proceeds = proceeds_for_shares(0, shares, B, shares, false).unwrap();
+        assert!(proceeds <= amount);
+        assert!(amount - proceeds <= 1);
+    }
+
+    #[test]
+    fn test_subsidy_covers_worst_case() {
+        let subsidy = max_loss(B).unwrap();
+        let shares = shares_for_cost(0, 0, B, 10 * B, true).unwrap();
+        let collected = (cost(shares, 0, B).unwrap() - cost(0, 0, B).unwrap()) / SCALE;
+        assert!(subsidy as i128 + collected >= shares as i128);
+        assert!(subsidy as i128 + collected - (shares as i128) < subsidy as i128);
+    }
+}
@@ -0,0 +1,94 @@
+use anchor_lang::prelude::*;
+use anchor_spl::metadata::mpl_token_metadata::types::DataV2;
+use anchor_spl::metadata::{create_metadata_accounts_v3, CreateMetadataAccountsV3};
+
+/// Accounts shared by every metadata CPI for the protocol's mints. The mint
+/// authority PDA, the market for outcome mints and the pool for LP mints,
+/// is also the metadata update authority.
+pub struct MetadataAccounts<'a, 'info> {
+    pub metadata_program: &'a AccountInfo<'info>,
+    pub authority: &'a AccountInfo<'info>,
+    pub payer: &'a AccountInfo<'info>,
+    pub system_program: &'a AccountInfo<'info>,
+    pub rent: &'a AccountInfo<'info>,
+}
+
+/// Characters of the market address kept in mint names, which metadata
+/// caps at 32 bytes.
+const NAME_ADDRESS_LEN: usize = 8;
+
+/// Name shown by wallets for a market mint, e.g. `ZENTRO-7xKXtg2C-YES`,
+/// from the start of the market address. Market ids can repeat, addresses
+/// can't.
+pub fn mint_name(market: &Pubkey, suffix: &str) -> String {
+    let address = market.to_string();
+    format!("ZENTRO-{}-{}", &address[..NAME_ADDRESS_LEN], suffix)
+}
+
+/// Metadata JSON location for a market mint under the protocol's base URI,
+/// keyed by the full market address.
+pub fn mint_uri(base_uri: &str, market: &Pubkey, suffix: &str) -> String {
+    format!(
+        "{}/{}/{}.json",
+        base_uri.trim_end_matches('/'),
+        market,
+        suffix.to_ascii_lowercase()
+    )
+}
+
+pub fn create_mint_metadata<'info>(
+    accounts: &MetadataAccounts<'_, 'info>,
+    metadata: &AccountInfo<'info>,
+    mint: &AccountInfo<'info>,
+    name: String,
+    symbol: String,
+    uri: String,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    create_metadata_accounts_v3(
+        CpiContext::new_with_signer(
+            accounts.metadata_program.clone(),
+            CreateMetadataAccountsV3 {
+                metadata: metadata.clone(),
+                mint: mint.clone(),
+                mint_authority: accounts.authority.clone(),
+                payer: accounts.payer.clone(),
+                update_authority: accounts.authority.clone(),
+                system_program: accounts.system_program.clone(),
+                rent: accounts.rent.clone(),
+            },
+            signer_seeds,
+        ),
+        DataV2 {
+            name,
+            symbol,
+            uri,
+            seller_fee_basis_points: 0,
+            creators: None,
+            collection: None,
+            uses: None,
+        },
+        true,
+        true,
+        None,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mint_name_and_uri() {
+        let market = Pubkey::new_from_array([7; 32]);
+        let address = market.to_string();
+
+        assert_eq!(mint_name(&market, "YES"), format!("ZENTRO-{}-YES", &address[..8]));
+        assert_eq!(mint_name(&market, "LP"), format!("ZENTRO-{}-LP", &address[..8]));
+        assert!(mint_name(&Pubkey::new_from_array([255; 32]), "YES").len() <= 32);
+        assert_eq!(
+            mint_uri("https://example.com/metadata/", &market, "NO"),
+            format!("https://example.com/metadata/{address}/no.json")
+        );
+    }
+}
@@ -0,0 +1,6 @@
+pub mod collateral;
+pub mod ed25519;
+pub mod fixed_point;
+pub mod lmsr;
+pub mod metadata;
+pub mod pricing;
\ No newline at end of file
@@ -2,6 +2,9 @@
 use anchor_lang::prelude::*;
 use std::cmp;
 
+use crate::utils::fixed_point::{mul_div, mul_div_u64, Rounding, BPS};
+use crate::ErrorCode;
+
 #[derive(Debug, Clone, Copy)]
 pub struct PricingParams {
     pub base_price: u64,
@@ -31,32 +34,29 @@ pub fn calculate_market_price(
         return Ok(params.base_price);
     }
 
-    let total_shares = yes_shares.saturating_add(no_shares);
+    let total_shares = yes_shares.checked_add(no_shares).ok_or(ErrorCode::MathOverflow)?;
     if total_shares == 0 {
         return Ok(params.base_price);
     }
 
     // Calculate probability based on share distribution
-    let yes_probability = (yes_shares as u128)
-        .saturating_mul(10000)
-        .saturating_div(total_shares as u128) as u64;
+    let yes_probability = mul_div_u64(yes_shares, BPS, total_shares, Rounding::Nearest)?;
 
     // Apply liquidity adjustment
-    let liquidity_factor = calculate_liquidity_factor(total_liquidity, params.liquidity_depth);
+    let liquidity_factor = calculate_liquidity_factor(total_liquidity, params.liquidity_depth)?;
     
     // Apply volatility adjustment
     let volatility_adjustment = calculate_volatility_adjustment(
         yes_shares,
         no_shares,
         params.volatility_factor,
-    );
+    )?;
 
-    let adjusted_price = yes_probability
-        .saturating_mul(liquidity_factor)
-        .saturating_div(10000)
-        .saturating_add(volatility_adjustment);
+    let adjusted_price = mul_div_u64(yes_probability, liquidity_factor, BPS, Rounding::Nearest)?
+        .checked_add(volatility_adjustment)
+        .ok_or(ErrorCode::MathOverflow)?;
 
-    Ok(cmp::min(adjusted_price, 10000))
+    Ok(cmp::min(adjusted_price, BPS))
 }
 
 pub fn calculate_buy_price(
@@ -65,24 +65,26 @@ pub fn calculate_buy_price(
     total_liquidity: u64,
     is_yes_side: bool,
 ) -> Result<u64> {
+    require!(current_price <= BPS, ErrorCode::InvalidPercentage);
     if shares_to_buy == 0 {
         return Ok(0);
     }
 
-    let base_cost = current_price.saturating_mul(shares_to_buy).saturating_div(10000);
+    // Costs round up so quotes never undercharge
+    let base_cost = mul_div_u64(current_price, shares_to_buy, BPS, Rounding::Up)?;
     
     // Calculate slippage based on order size relative to liquidity
-    let slippage = calculate_slippage(shares_to_buy, total_liquidity);
+    let slippage = calculate_slippage(shares_to_buy, total_liquidity)?;
     
-    let slippage_cost = base_cost.saturating_mul(slippage).saturating_div(10000);
+    let slippage_cost = mul_div_u64(base_cost, slippage, BPS, Rounding::Up)?;
     
-    if is_yes_side {
-        Ok(base_cost.saturating_add(slippage_cost))
+    let side_cost = if is_yes_side {
+        base_cost
     } else {
-        let inverse_price = 10000_u64.saturating_sub(current_price);
-        let inverse_cost = inverse_price.saturating_mul(shares_to_buy).saturating_div(10000);
-        Ok(inverse_cost.saturating_add(slippage_cost))
-    }
+        mul_div_u64(BPS - current_price, shares_to_buy, BPS, Rounding::Up)?
+    };
+
+    side_cost.checked_add(slippage_cost).ok_or(error!(ErrorCode::MathOverflow))
 }
 
 pub fn calculate_sell_price(
@@ -91,84 +93,77 @@ pub fn calculate_sell_price(
     total_liquidity: u64,
     is_yes_side: bool,
 ) -> Result<u64> {
+    require!(current_price <= BPS, ErrorCode::InvalidPercentage);
     if shares_to_sell == 0 {
         return Ok(0);
     }
 
-    let base_value = if is_yes_side {
-        current_price.saturating_mul(shares_to_sell).saturating_div(10000)
-    } else {
-        let inverse_price = 10000_u64.saturating_sub(current_price);
-        inverse_price.saturating_mul(shares_to_sell).saturating_div(10000)
-    };
+    // Proceeds round down so quotes never overpay
+    let side_price = if is_yes_side { current_price } else { BPS - current_price };
+    let base_value = mul_div_u64(side_price, shares_to_sell, BPS, Rounding::Down)?;
     
     // Calculate slippage (negative for selling)
-    let slippage = calculate_slippage(shares_to_sell, total_liquidity);
-    let slippage_reduction = base_value.saturating_mul(slippage).saturating_div(10000);
+    let slippage = calculate_slippage(shares_to_sell, total_liquidity)?;
+    let slippage_reduction = mul_div_u64(base_value, slippage, BPS, Rounding::Up)?;
     
-    Ok(base_value.saturating_sub(slippage_reduction))
+    // Slippage is capped below 100%, so this cannot underflow
+    Ok(base_value - slippage_reduction)
 }
 
-fn calculate_liquidity_factor(current_liquidity: u64, target_liquidity: u64) -> u64 {
+fn calculate_liquidity_factor(current_liquidity: u64, target_liquidity: u64) -> Result<u64> {
     if target_liquidity == 0 {
-        return 10000;
+        return Ok(BPS);
     }
 
-    let ratio = (current_liquidity as u128)
-        .saturating_mul(10000)
-        .saturating_div(target_liquidity as u128) as u64;
+    // Liquidity factor ranges from 8000 (low liquidity) to 12000 (high
+    // liquidity), reaching the top at the target depth
+    let capped_liquidity = cmp::min(current_liquidity, target_liquidity);
+    let bonus = mul_div_u64(capped_liquidity, 4000, target_liquidity, Rounding::Down)?;
 
-    // Liquidity factor ranges from 8000 (low liquidity) to 12000 (high liquidity)
-    cmp::max(8000, cmp::min(12000, 8000_u64.saturating_add(ratio.saturating_mul(4000).saturating_div(10000))))
+    Ok(8000 + bonus)
 }
 
-fn calculate_volatility_adjustment(yes_shares: u64, no_shares: u64, volatility_factor: u64) -> u64 {
-    let total_shares = yes_shares.saturating_add(no_shares);
+fn calculate_volatility_adjustment(yes_shares: u64, no_shares: u64, volatility_factor: u64) -> Result<u64> {
+    let total_shares = yes_shares.checked_add(no_shares).ok_or(ErrorCode::MathOverflow)?;
     if total_shares == 0 {
-        return 0;
+        return Ok(0);
     }
 
-    let imbalance = if yes_shares > no_shares {
-        yes_shares.saturating_sub(no_shares)
-    } else {
-        no_shares.saturating_sub(yes_shares)
-    };
+    let imbalance = yes_shares.abs_diff(no_shares);
+    let imbalance_ratio = mul_div_u64(imbalance, BPS, total_shares, Rounding::Down)?;
 
-    let imbalance_ratio = (imbalance as u128)
-        .saturating_mul(10000)
-        .saturating_div(total_shares as u128) as u64;
-
-    imbalance_ratio
-        .saturating_mul(volatility_factor)
-        .saturating_div(10000)
+    mul_div_u64(imbalance_ratio, volatility_factor, BPS, Rounding::Down)
 }
 
-fn calculate_slippage(order_size: u64, total_liquidity: u64) -> u64 {
+fn calculate_slippage(order_size: u64, total_liquidity: u64) -> Result<u64> {
     if total_liquidity == 0 {
-        return 500; // 5% default slippage
+        return Ok(500); // 5% default slippage
     }
 
-    let order_ratio = (order_size as u128)
-        .saturating_mul(10000)
-        .saturating_div(total_liquidity as u128) as u64;
+    // Orders at least as large as the pool already hit the cap below
+    let order_ratio = cmp::min(
+        mul_div(order_size as u128, BPS as u128, total_liquidity as u128, Rounding::Down)?,
+        BPS as u128,
+    ) as u64;
 
     // Slippage increases quadratically with order size
-    let base_slippage = order_ratio.saturating_mul(order_ratio).saturating_div(10000);
+    let base_slippage = order_ratio * order_ratio / BPS;
     
     // Cap slippage at 10%
-    cmp::min(base_slippage, 1000)
+    Ok(cmp::min(base_slippage, 1000))
 }
 
 pub fn calculate_payout_odds(current_price: u64) -> (u64, u64) {
     if current_price == 0 {
         return (0, 10000);
     }
-    if current_price >= 10000 {
-        return (10000, 0);
+    if current_price >= BPS {
+        return (BPS, 0);
     }
 
-    let yes_odds = 10000_u64.saturating_mul(10000).saturating_div(current_price);
-    let no_odds = 10000_u64.saturating_mul(10000).saturating_div(10000_u64.saturating_sub(current_price));
+    // 0 < current_price < BPS, so neither division can overflow or be by zero
+    let yes_odds = BPS * BPS / current_price;
+    let no_odds = BPS * BPS / (BPS - current_price);
 
     (yes_odds, no_odds)
 }
@@ -180,13 +175,15 @@ pub fn calculate_expected_return(
 ) -> Result<u64> {
     let (yes_odds, no_odds) = calculate_payout_odds(current_price);
     
-    let potential_return = if predicted_outcome {
-        investment.saturating_mul(yes_odds).saturating_div(10000)
-    } else {
-        investment.saturating_mul(no_odds).saturating_div(10000)
-    };
+    let odds = if predicted_outcome { yes_odds } else { no_odds };
+    // Odds of zero mean the outcome can't pay out at all
+    if odds == 0 {
+        return Ok(0);
+    }
+    let potential_return = mul_div_u64(investment, odds, BPS, Rounding::Down)?;
 
-    Ok(potential_return.saturating_sub(investment))
+    // Any other odds pay back at least the investment
+    potential_return.checked_sub(investment).ok_or(error!(ErrorCode::MathOverflow))
 }
 
 #[cfg(test)]
@@ -197,9 +194,10 @@ mod tests {
     fn test_calculate_market_price() {
         let params = PricingParams::default();
         
-        // Equal shares should result in ~50% price
+        // Equal shares give 50%, scaled by the 8,400 liquidity factor of a
+        // pool at a tenth of the target depth
         let price = calculate_market_price(1000, 1000, 100000, &params).unwrap();
-        assert!(price >= 4500 && price <= 5500);
+        assert_eq!(price, 4200);
         
         // More yes shares should increase price
         let price_yes_heavy = calculate_market_price(2000, 1000, 100000, &params).unwrap();
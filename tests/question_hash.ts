@@ -0,0 +1,23 @@
+import { strict as assert } from 'assert';
+import { PublicKey } from '@solana/web3.js';
+
+import { getQuestionHash, normalizeQuestion } from '../app/src/utils/program';
+import vectors from '../programs/zentro/tests/fixtures/question_hash.json';
+
+// The program's question_hash test checks the same vectors, so a market
+// address derived here matches the one the program creates.
+describe('question hash', () => {
+  for (const vector of vectors) {
+    it(`hashes ${JSON.stringify(vector.question)} like the program`, async () => {
+      assert.equal(normalizeQuestion(vector.question), vector.normalized);
+
+      const hash = await getQuestionHash(
+        vector.question,
+        vector.endTime,
+        new PublicKey(vector.oracle),
+        new PublicKey(vector.collateralMint)
+      );
+      assert.equal(hash.toString('hex'), vector.hash);
+    });
+  }
+});